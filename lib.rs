@@ -1,13 +1,40 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, keccak::hashv};
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
 declare_id!("YourProgramID");
 
+// Shared with the launchpad's allowlist proofs
+#[path = "utils/merkle.rs"]
+pub mod merkle;
+pub use merkle::verify_merkle_proof;
+
 // Constants
 pub const SECONDS_IN_DAY: i64 = 86400;
 pub const MINIMUM_VESTING_PERIOD: i64 = SECONDS_IN_DAY * 7;    // 1 week
 pub const MAXIMUM_VESTING_PERIOD: i64 = SECONDS_IN_DAY * 365 * 2;  // 2 years
 pub const MINIMUM_AMOUNT: u64 = 1;
+pub const MAX_MILESTONES: usize = 8;
+pub const MAX_LOCK_POSITIONS: usize = 32;
+pub const MAX_DISTRIBUTOR_RECIPIENTS: u32 = 64_000;
+pub const MIN_TWAP_WINDOW: i64 = 60 * 60;                        // 1 hour
+pub const MIN_OBSERVATION_INTERVAL: i64 = 60;                    // 1 minute between market cap observations
+
+// Programs the market cap is read from
+pub mod launchpad {
+    use super::*;
+    declare_id!("GVapdHoG4xjJZpvGPd8EUBaUJKR5Txpf6VHnVwBVCY69");
+}
+
+pub mod dynamic_amm {
+    use super::*;
+    declare_id!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB");
+}
+
+pub mod dynamic_vault {
+    use super::*;
+    declare_id!("24Uqj9JCLxUeoC3hGfh5W3s9FM9uCHDS2SG3LYwBpyTi");
+}
 
 #[program]
 pub mod complete_solana_project {
@@ -17,8 +44,9 @@ pub mod complete_solana_project {
         let vault = &mut ctx.accounts.vault;
         vault.owner = *ctx.accounts.payer.key;
         vault.bump = bump;
-        vault.locked_amount = 0;
-        vault.locked_until = 0;
+        vault.total_locked = 0;
+        vault.next_nonce = 0;
+        vault.positions = Vec::new();
 
         emit!(VaultInitialized {
             owner: vault.owner,
@@ -30,40 +58,137 @@ pub mod complete_solana_project {
     pub fn lock_tokens(ctx: Context<LockTokens>, amount: u64, lock_duration: i64) -> Result<()> {
         require!(amount > MINIMUM_AMOUNT, CustomError::InvalidAmount);
         require!(
-            lock_duration >= MINIMUM_VESTING_PERIOD 
-                && lock_duration <= MAXIMUM_VESTING_PERIOD,
+            (MINIMUM_VESTING_PERIOD..=MAXIMUM_VESTING_PERIOD).contains(&lock_duration),
+            CustomError::InvalidTimeParameters
+        );
+
+        ctx.accounts.open_position(
+            amount,
+            lock_duration,
+            *ctx.bumps.get("lock_position").unwrap(),
+            Pubkey::default(),
+        )
+    }
+
+    // Locks LP tokens of a pool a launchpad bonding curve migrated into
+    pub fn lock_lp_tokens(ctx: Context<LockLpTokens>, amount: u64, lock_duration: i64) -> Result<()> {
+        require!(amount > MINIMUM_AMOUNT, CustomError::InvalidAmount);
+        require!(
+            (MINIMUM_VESTING_PERIOD..=MAXIMUM_VESTING_PERIOD).contains(&lock_duration),
             CustomError::InvalidTimeParameters
         );
 
+        let lp_mint = launchpad_pool_lp_mint(&ctx.accounts.bonding_curve, &ctx.accounts.pool)?;
+        require_keys_eq!(
+            ctx.accounts.lock.user_token_account.mint,
+            lp_mint,
+            CustomError::InvalidMarketAccount
+        );
+
+        ctx.accounts.lock.open_position(
+            amount,
+            lock_duration,
+            *ctx.bumps.get("lock_position").unwrap(),
+            ctx.accounts.pool.key(),
+        )
+    }
+
+    pub fn extend_lock(ctx: Context<ExtendLock>, new_unlock_time: i64) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
-        let unlock_time = current_time
-            .checked_add(lock_duration)
-            .ok_or(CustomError::CalculationError)?;
+        let lock_position = &mut ctx.accounts.lock_position;
 
-        // Transfer from the user's token account into the vault's token account.
-        token::transfer(ctx.accounts.into_transfer_to_vault_context(), amount)?;
+        // Unlock times only ever move later
+        require!(new_unlock_time > lock_position.locked_until, CustomError::InvalidUnlockTime);
+        require!(
+            new_unlock_time
+                .checked_sub(current_time)
+                .ok_or(CustomError::CalculationError)?
+                <= MAXIMUM_VESTING_PERIOD,
+            CustomError::InvalidTimeParameters
+        );
+
+        let previous_unlock_time = lock_position.locked_until;
+        lock_position.locked_until = new_unlock_time;
+
+        emit!(LockExtended {
+            lock_position: lock_position.key(),
+            owner: lock_position.owner,
+            previous_unlock_time,
+            new_unlock_time,
+        });
+        Ok(())
+    }
 
+    pub fn transfer_lock_ownership(ctx: Context<TransferLockOwnership>, new_owner: Pubkey) -> Result<()> {
+        let amount = ctx.accounts.lock_position.amount;
+        let lock_position_key = ctx.accounts.lock_position.key();
+
+        // The tokens move with the position into the new owner's vault, the unlock time is kept
         let vault = &mut ctx.accounts.vault;
-        vault.locked_until = unlock_time;
-        vault.locked_amount = amount;
+        let seeds = &[
+            b"vault",
+            vault.owner.as_ref(),
+            &[vault.bump],
+        ];
+        let signer = &[&seeds[..]];
 
-        emit!(TokensLocked {
-            vault_owner: vault.owner,
-            locker_authority: ctx.accounts.authority.key(),
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.new_vault_token_account.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        vault.positions.retain(|position| *position != lock_position_key);
+        vault.total_locked = vault.total_locked
+            .checked_sub(amount)
+            .ok_or(CustomError::CalculationError)?;
+
+        let new_vault = &mut ctx.accounts.new_vault;
+        if new_vault.owner == Pubkey::default() {
+            new_vault.owner = new_owner;
+            new_vault.bump = *ctx.bumps.get("new_vault").unwrap();
+
+            emit!(VaultInitialized {
+                owner: new_owner,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        require!(new_vault.positions.len() < MAX_LOCK_POSITIONS, CustomError::TooManyLockPositions);
+        new_vault.positions.push(lock_position_key);
+        new_vault.total_locked = new_vault.total_locked
+            .checked_add(amount)
+            .ok_or(CustomError::CalculationError)?;
+
+        let lock_position = &mut ctx.accounts.lock_position;
+        let previous_owner = lock_position.owner;
+        lock_position.owner = new_owner;
+        lock_position.vault = new_vault.key();
+
+        emit!(LockOwnershipTransferred {
+            lock_position: lock_position_key,
+            previous_owner,
+            new_owner,
             amount,
-            lock_until: unlock_time,
+            locked_until: lock_position.locked_until,
         });
         Ok(())
     }
 
     pub fn unlock_tokens(ctx: Context<UnlockTokens>) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
-        let vault = &mut ctx.accounts.vault;
+        let lock_position = &ctx.accounts.lock_position;
 
-        require!(current_time >= vault.locked_until, CustomError::TokensStillLocked);
-        require!(vault.locked_amount > 0, CustomError::InsufficientBalance);
+        require!(current_time >= lock_position.locked_until, CustomError::TokensStillLocked);
+        require!(lock_position.amount > 0, CustomError::InsufficientBalance);
 
-        let amount = vault.locked_amount;
+        let amount = lock_position.amount;
+        let vault = &mut ctx.accounts.vault;
 
         // Because the vault is a PDA, we must sign with the vault seeds to transfer tokens out.
         let vault_bump = vault.bump;
@@ -77,7 +202,7 @@ pub mod complete_solana_project {
         let cpi_accounts = token::Transfer {
             from: ctx.accounts.vault_token_account.to_account_info(),
             to: ctx.accounts.user_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(), // The PDA
+            authority: vault.to_account_info(), // The PDA
         };
         let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
@@ -86,41 +211,86 @@ pub mod complete_solana_project {
         );
         token::transfer(cpi_ctx, amount)?;
 
-        vault.locked_amount = 0;
+        // The position is closed, so it leaves the vault's index too
+        let lock_position_key = ctx.accounts.lock_position.key();
+        vault.positions.retain(|position| *position != lock_position_key);
+        vault.total_locked = vault.total_locked
+            .checked_sub(amount)
+            .ok_or(CustomError::CalculationError)?;
 
         emit!(TokensUnlocked {
             vault_owner: vault.owner,
             unlocker_authority: ctx.accounts.authority.key(),
+            lock_position: lock_position_key,
             amount,
             timestamp: current_time,
         });
         Ok(())
     }
 
-    pub fn initialize_vesting(
-        ctx: Context<InitializeVesting>,
-        amount: u64,
-        start_time: i64,
-        end_time: i64,
-        target_market_cap: u64,
-    ) -> Result<()> {
+    pub fn initialize_vesting(ctx: Context<InitializeVesting>, args: InitializeVestingArgs) -> Result<()> {
+        let InitializeVestingArgs {
+            amount,
+            start_time,
+            end_time,
+            target_market_cap,
+            schedule_type,
+            cliff_period,
+            stages,
+            milestones,
+            twap_window,
+            market_cap_milestones,
+            revocable,
+        } = args;
+
         require!(amount > MINIMUM_AMOUNT, CustomError::InvalidVestingAmount);
-        require!(end_time > start_time, CustomError::InvalidTimeParameters);
+        require!(twap_window >= 0, CustomError::InvalidTimeParameters);
 
         let current_time = Clock::get()?.unix_timestamp;
         require!(start_time > current_time, CustomError::InvalidTimeParameters);
 
-        let vesting_duration = end_time
-            .checked_sub(start_time)
-            .ok_or(CustomError::CalculationError)?;
-        require!(
-            vesting_duration >= MINIMUM_VESTING_PERIOD 
-                && vesting_duration <= MAXIMUM_VESTING_PERIOD,
-            CustomError::InvalidTimeParameters
-        );
+        let terms = VestingTerms {
+            schedule_type,
+            start_time,
+            end_time,
+            cliff_period,
+            stages,
+            milestones,
+        };
+        terms.validate()?;
+        let vesting_duration = end_time - start_time;
+
+        // Market cap milestones are optional, when set they are ascending and unlock 100% between them
+        if !market_cap_milestones.is_empty() {
+            require!(
+                market_cap_milestones.len() <= MAX_MILESTONES
+                    && market_cap_milestones.windows(2).all(|pair| pair[0].target_cap < pair[1].target_cap)
+                    && market_cap_milestones.iter().all(|m| m.unlock_percentage > 0 && !m.is_reached),
+                CustomError::InvalidMilestone
+            );
+            let total_percentage: u16 = market_cap_milestones.iter()
+                .map(|m| m.unlock_percentage as u16)
+                .sum();
+            require!(total_percentage == 100, CustomError::InvalidMilestone);
+        }
+
+        // A market cap condition can only be read from a launchpad curve that migrates to dynamic_amm,
+        // and is checked against a TWAP so a single trade can't meet it
+        if target_market_cap > 0 || twap_window > 0 || !market_cap_milestones.is_empty() {
+            require!(
+                (MIN_TWAP_WINDOW..=vesting_duration).contains(&twap_window),
+                CustomError::InvalidTimeParameters
+            );
+            let bonding_curve = ctx.accounts.bonding_curve.as_ref().ok_or(CustomError::UnsupportedMarketCapSource)?;
+            require_launchpad_curve(bonding_curve, &ctx.accounts.token_mint.key())?;
+            let curve_data = bonding_curve.try_borrow_data()?;
+            let migration_target = *curve_data.get(BONDING_CURVE_MIGRATION_TARGET).ok_or(CustomError::InvalidMarketAccount)?;
+            require!(migration_target == MIGRATION_TARGET_DYNAMIC_AMM, CustomError::UnsupportedMarketCapSource);
+        }
 
         let vesting = &mut ctx.accounts.vesting;
-        vesting.owner = ctx.accounts.owner.key();
+        vesting.grantor = ctx.accounts.grantor.key();
+        vesting.beneficiary = ctx.accounts.beneficiary.key();
         vesting.token_mint = ctx.accounts.token_mint.key();
         vesting.amount = amount;
         vesting.start_time = start_time;
@@ -128,9 +298,20 @@ pub mod complete_solana_project {
         vesting.target_market_cap = target_market_cap;
         vesting.is_locked = true;
         vesting.bump = *ctx.bumps.get("vesting").unwrap();
+        vesting.schedule_type = schedule_type;
+        vesting.cliff_period = cliff_period;
+        vesting.stages = stages;
+        vesting.milestones = terms.milestones;
+        vesting.released_amount = 0;
+        vesting.twap_window = twap_window;
+        vesting.twap = MarketCapTwap::default();
+        vesting.market_cap_milestones = market_cap_milestones;
+        vesting.revocable = revocable;
+        vesting.revoked_at = 0;
 
         emit!(VestingInitialized {
-            owner: vesting.owner,
+            grantor: vesting.grantor,
+            beneficiary: vesting.beneficiary,
             amount,
             start_time,
             end_time,
@@ -142,52 +323,386 @@ pub mod complete_solana_project {
         let vesting = &mut ctx.accounts.vesting;
         require!(amount == vesting.amount, CustomError::InvalidVestingAmount);
         require!(vesting.is_locked, CustomError::TokensAlreadyUnlocked);
+        require!(vesting.revoked_at == 0, CustomError::VestingRevoked);
 
         token::transfer(ctx.accounts.into_transfer_to_vesting_context(), amount)?;
 
         emit!(VestingTokensLocked {
-            owner: ctx.accounts.owner.key(),
+            grantor: ctx.accounts.grantor.key(),
             amount,
             vesting_account: ctx.accounts.vesting.key(),
         });
         Ok(())
     }
 
-    pub fn unlock_vested_tokens(ctx: Context<UnlockVestedTokens>, current_market_cap: u64) -> Result<()> {
+    pub fn record_market_cap(ctx: Context<RecordMarketCap>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let market_cap = ctx.accounts.market.market_cap()?;
+
         let vesting = &mut ctx.accounts.vesting;
+        require!(vesting.tracks_market_cap(), CustomError::MarketCapNotTracked);
+        require!(vesting.twap.accepts_observation(current_time), CustomError::ObservationTooSoon);
+        let twap_market_cap = vesting.observe_market_cap(market_cap, current_time)?;
+        let reached = vesting.reach_market_cap_milestones(twap_market_cap);
+
+        emit!(MarketCapRecorded {
+            vesting: vesting.key(),
+            market_cap,
+            twap_market_cap: vesting.twap.twap_market_cap,
+            timestamp: current_time,
+        });
+        emit_milestones_reached(vesting.key(), &reached, twap_market_cap.unwrap_or(0), current_time);
+        Ok(())
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
+        let vesting = &mut ctx.accounts.vesting;
 
         require!(vesting.is_locked, CustomError::TokensAlreadyUnlocked);
-        require!(current_time >= vesting.end_time, CustomError::VestingPeriodNotEnded);
-        require!(
-            current_market_cap >= vesting.target_market_cap,
-            CustomError::MarketCapNotReached
-        );
+
+        // The market cap is only read when a condition depends on it, revocation freezes the conditions
+        if vesting.tracks_market_cap() && vesting.revoked_at == 0 {
+            let market_cap = ctx.accounts.market.market_cap()?;
+            let twap_market_cap = vesting.observe_market_cap(market_cap, current_time)?;
+            let reached = vesting.reach_market_cap_milestones(twap_market_cap);
+            emit_milestones_reached(vesting.key(), &reached, twap_market_cap.unwrap_or(0), current_time);
+
+            // Market cap milestones replace the single target
+            if vesting.market_cap_milestones.is_empty() {
+                require!(
+                    matches!(twap_market_cap, Some(cap) if cap >= vesting.target_market_cap),
+                    CustomError::MarketCapNotReached
+                );
+            }
+        }
+
+        // Only what vested since the last claim is released
+        let vested_amount = vesting.claimable_vested_amount(current_time)?;
+        let claimable = vested_amount
+            .checked_sub(vesting.released_amount)
+            .ok_or(CustomError::CalculationError)?;
+        require!(claimable > 0, CustomError::NothingToClaim);
 
         // Sign with the vesting PDA seeds
         let seeds = &[
             b"vesting",
             vesting.token_mint.as_ref(),
-            vesting.owner.as_ref(),
+            vesting.grantor.as_ref(),
+            vesting.beneficiary.as_ref(),
             &[vesting.bump],
         ];
         let signer = &[&seeds[..]];
 
         let cpi_accounts = token::Transfer {
             from: ctx.accounts.vesting_token_account.to_account_info(),
-            to: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.beneficiary_token_account.to_account_info(),
             authority: vesting.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
         
-        token::transfer(cpi_ctx, vesting.amount)?;
+        token::transfer(cpi_ctx, claimable)?;
+
+        vesting.released_amount = vested_amount;
+        if vesting.released_amount == vesting.final_vested_amount()? {
+            vesting.is_locked = false;
+        }
+
+        emit!(VestedTokensClaimed {
+            beneficiary: ctx.accounts.beneficiary.key(),
+            amount: claimable,
+            released_amount: vesting.released_amount,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let vesting = &mut ctx.accounts.vesting;
+
+        require!(vesting.revocable, CustomError::VestingNotRevocable);
+        require!(vesting.revoked_at == 0, CustomError::VestingRevoked);
+        require!(vesting.is_locked, CustomError::TokensAlreadyUnlocked);
+
+        // What vested so far and the market cap conditions unlocked stays claimable by the beneficiary,
+        // the rest goes back to the grantor
+        vesting.revoked_at = current_time;
+        let vested_amount = vesting.final_vested_amount()?;
+        let refund = vesting.amount
+            .checked_sub(vested_amount)
+            .ok_or(CustomError::CalculationError)?;
+
+        if refund > 0 {
+            let seeds = &[
+                b"vesting",
+                vesting.token_mint.as_ref(),
+                vesting.grantor.as_ref(),
+                vesting.beneficiary.as_ref(),
+                &[vesting.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = token::Transfer {
+                from: ctx.accounts.vesting_token_account.to_account_info(),
+                to: ctx.accounts.grantor_token_account.to_account_info(),
+                authority: vesting.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            );
+            token::transfer(cpi_ctx, refund)?;
+        }
+
+        if vesting.released_amount == vested_amount {
+            vesting.is_locked = false;
+        }
+
+        emit!(VestingRevoked {
+            vesting: vesting.key(),
+            grantor: vesting.grantor,
+            beneficiary: vesting.beneficiary,
+            vested_amount,
+            refund,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn create_distributor(
+        ctx: Context<CreateDistributor>,
+        merkle_root: [u8; 32],
+        num_recipients: u32,
+        total_amount: u64,
+    ) -> Result<()> {
+        require!(total_amount > MINIMUM_AMOUNT, CustomError::InvalidAmount);
+        require!(
+            num_recipients > 0 && num_recipients <= MAX_DISTRIBUTOR_RECIPIENTS,
+            CustomError::InvalidRecipients
+        );
+
+        token::transfer(ctx.accounts.into_transfer_to_distributor_context(), total_amount)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.authority = ctx.accounts.authority.key();
+        distributor.token_mint = ctx.accounts.token_mint.key();
+        distributor.merkle_root = merkle_root;
+        distributor.total_amount = total_amount;
+        distributor.claimed_amount = 0;
+        distributor.num_recipients = num_recipients;
+        distributor.bump = *ctx.bumps.get("distributor").unwrap();
+        distributor.claimed_bitmap = vec![0; Distributor::bitmap_len(num_recipients)];
+
+        emit!(DistributorCreated {
+            distributor: distributor.key(),
+            authority: distributor.authority,
+            token_mint: distributor.token_mint,
+            merkle_root,
+            num_recipients,
+            total_amount,
+        });
+        Ok(())
+    }
+
+    pub fn claim_distribution(
+        ctx: Context<ClaimDistribution>,
+        index: u32,
+        amount: u64,
+        terms: VestingTerms,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let recipient = ctx.accounts.recipient.key();
+        let distributor = &mut ctx.accounts.distributor;
+
+        require!(index < distributor.num_recipients, CustomError::InvalidRecipients);
+        require!(!distributor.is_claimed(index), CustomError::AlreadyClaimed);
+
+        let leaf = distribution_leaf(index, &recipient, amount, &terms)?;
+        require!(
+            verify_merkle_proof(&proof, &distributor.merkle_root, leaf),
+            CustomError::InvalidProof
+        );
+        // A leaf's terms get the same checks as a vesting account's, so a bad tree can't lock a claim
+        terms.validate()?;
+
+        // The claim record tracks partial claims of a time-released leaf
+        let claim = &mut ctx.accounts.claim;
+        claim.distributor = distributor.key();
+        claim.recipient = recipient;
+        claim.index = index;
+        claim.bump = *ctx.bumps.get("claim").unwrap();
+
+        let vested_amount = terms.vested_amount(amount, current_time)?;
+        let claimable = vested_amount
+            .checked_sub(claim.released_amount)
+            .ok_or(CustomError::CalculationError)?;
+        require!(claimable > 0, CustomError::NothingToClaim);
+
+        let seeds = &[
+            b"distributor",
+            distributor.token_mint.as_ref(),
+            distributor.merkle_root.as_ref(),
+            &[distributor.bump],
+        ];
+        let signer = &[&seeds[..]];
 
-        vesting.is_locked = false;
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.distributor_token_account.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: distributor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, claimable)?;
+
+        claim.released_amount = vested_amount;
+        distributor.claimed_amount = distributor.claimed_amount
+            .checked_add(claimable)
+            .ok_or(CustomError::CalculationError)?;
+
+        // A fully claimed leaf is marked in the bitmap, so the closed record can't be opened again
+        if vested_amount == amount {
+            distributor.set_claimed(index);
+            ctx.accounts.claim.close(ctx.accounts.recipient.to_account_info())?;
+        }
+
+        emit!(DistributionClaimed {
+            distributor: ctx.accounts.distributor.key(),
+            recipient,
+            index,
+            amount: claimable,
+            released_amount: vested_amount,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        amount: u64,
+        rate: u64,
+        interval: i64,
+        start_time: i64,
+    ) -> Result<()> {
+        require!(amount > MINIMUM_AMOUNT, CustomError::InvalidAmount);
+        require!(rate > 0 && interval > 0, CustomError::InvalidStreamRate);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(start_time >= current_time, CustomError::InvalidTimeParameters);
+
+        token::transfer(ctx.accounts.into_transfer_to_stream_context(), amount)?;
+
+        let stream = &mut ctx.accounts.stream;
+        stream.funder = ctx.accounts.funder.key();
+        stream.recipient = ctx.accounts.recipient.key();
+        stream.token_mint = ctx.accounts.token_mint.key();
+        stream.rate = rate;
+        stream.interval = interval;
+        stream.start_time = start_time;
+        stream.last_update_time = start_time;
+        stream.deposited_amount = amount;
+        stream.total_streamed = 0;
+        stream.withdrawn_amount = 0;
+        stream.is_cancelled = false;
+        stream.bump = *ctx.bumps.get("stream").unwrap();
+
+        emit!(StreamCreated {
+            stream: stream.key(),
+            funder: stream.funder,
+            recipient: stream.recipient,
+            amount,
+            rate,
+            interval,
+            start_time,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_from_stream(ctx: Context<WithdrawFromStream>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let stream = &mut ctx.accounts.stream;
+
+        stream.settle(current_time)?;
+        let amount = stream.total_streamed
+            .checked_sub(stream.withdrawn_amount)
+            .ok_or(CustomError::CalculationError)?;
+        require!(amount > 0, CustomError::NothingToClaim);
+
+        transfer_from_stream(
+            stream,
+            &ctx.accounts.stream_token_account,
+            &ctx.accounts.recipient_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        stream.withdrawn_amount = stream.total_streamed;
 
-        emit!(VestingTokensUnlocked {
-            owner: ctx.accounts.owner.key(),
-            amount: vesting.amount,
+        emit!(StreamWithdrawn {
+            stream: stream.key(),
+            recipient: stream.recipient,
+            amount,
+            withdrawn_amount: stream.withdrawn_amount,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn top_up_stream(ctx: Context<TopUpStream>, amount: u64) -> Result<()> {
+        require!(amount > 0, CustomError::InvalidAmount);
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // Settle first so a stream that ran dry doesn't accrue for the time it was empty
+        ctx.accounts.stream.settle(current_time)?;
+        token::transfer(ctx.accounts.into_transfer_to_stream_context(), amount)?;
+
+        let stream = &mut ctx.accounts.stream;
+        stream.deposited_amount = stream.deposited_amount
+            .checked_add(amount)
+            .ok_or(CustomError::CalculationError)?;
+
+        emit!(StreamToppedUp {
+            stream: stream.key(),
+            funder: stream.funder,
+            amount,
+            deposited_amount: stream.deposited_amount,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let stream = &mut ctx.accounts.stream;
+
+        // What accrued stays for the recipient to withdraw, the rest goes back to the funder
+        stream.settle(current_time)?;
+        let refund = stream.deposited_amount
+            .checked_sub(stream.total_streamed)
+            .ok_or(CustomError::CalculationError)?;
+        if refund > 0 {
+            transfer_from_stream(
+                stream,
+                &ctx.accounts.stream_token_account,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                refund,
+            )?;
+        }
+        stream.deposited_amount = stream.total_streamed;
+        stream.is_cancelled = true;
+
+        emit!(StreamCancelled {
+            stream: stream.key(),
+            funder: stream.funder,
+            refund,
+            total_streamed: stream.total_streamed,
             timestamp: current_time,
         });
         Ok(())
@@ -222,21 +737,108 @@ pub struct LockTokens<'info> {
     )]
     pub vault: Account<'info, Vault>,
 
+    // New position, seeded by the owner and the vault's next nonce
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LockPosition::LEN,
+        seeds = [b"lock", owner.key().as_ref(), vault.next_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lock_position: Account<'info, LockPosition>,
+
     // The user (authority) is transferring tokens from their TokenAccount
     #[account(mut)]
     pub user_token_account: Account<'info, TokenAccount>,
 
     // PDA vault’s token account
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = user_token_account.mint,
+        token::authority = vault
+    )]
     pub vault_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 
-    /// The actual signer doing the token transfer in (e.g. the user)
+    /// The actual signer doing the token transfer in (e.g. the user), pays for the position
+    #[account(mut)]
     pub authority: Signer<'info>,
 
     /// Must match the vault.owner
     pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LockLpTokens<'info> {
+    pub lock: LockTokens<'info>,
+
+    /// CHECK: Launchpad bonding curve, owner and PDA are validated in the handler
+    pub bonding_curve: UncheckedAccount<'info>,
+
+    /// CHECK: dynamic_amm pool the curve migrated into, validated in the handler
+    pub pool: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ CustomError::UnauthorizedAccess
+    )]
+    pub lock_position: Account<'info, LockPosition>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferLockOwnership<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ CustomError::UnauthorizedAccess
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        has_one = owner @ CustomError::UnauthorizedAccess,
+        has_one = vault @ CustomError::UnauthorizedAccess
+    )]
+    pub lock_position: Account<'info, LockPosition>,
+
+    #[account(
+        mut,
+        token::mint = lock_position.token_mint,
+        token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    // The new owner's vault takes over the position and its tokens, created here if the new owner has none
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", new_owner.as_ref()],
+        bump,
+        constraint = new_vault.key() != vault.key() @ CustomError::UnauthorizedAccess
+    )]
+    pub new_vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        token::mint = lock_position.token_mint,
+        token::authority = new_vault
+    )]
+    pub new_vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -247,10 +849,19 @@ pub struct UnlockTokens<'info> {
     )]
     pub vault: Account<'info, Vault>,
 
-    #[account(mut)]
+    // Closed on unlock, the rent goes back to the owner
+    #[account(
+        mut,
+        has_one = owner @ CustomError::UnauthorizedAccess,
+        has_one = vault @ CustomError::UnauthorizedAccess,
+        close = owner
+    )]
+    pub lock_position: Account<'info, LockPosition>,
+
+    #[account(mut, token::mint = lock_position.token_mint)]
     pub user_token_account: Account<'info, TokenAccount>,   // Tokens go back here
 
-    #[account(mut)]
+    #[account(mut, token::authority = vault)]
     pub vault_token_account: Account<'info, TokenAccount>,  // Vault's token account
 
     pub token_program: Program<'info, Token>,
@@ -259,21 +870,28 @@ pub struct UnlockTokens<'info> {
     pub authority: Signer<'info>,
 
     /// Must match the vault.owner
+    #[account(mut)]
     pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct InitializeVesting<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub grantor: Signer<'info>,
+
+    /// CHECK: Only recorded as the account the vested tokens are claimable by
+    pub beneficiary: UncheckedAccount<'info>,
 
     pub token_mint: Account<'info, Mint>,
 
+    /// CHECK: Launchpad bonding curve of the mint, only needed for a market cap condition and validated in the handler
+    pub bonding_curve: Option<UncheckedAccount<'info>>,
+
     #[account(
         init,
-        payer = owner,
+        payer = grantor,
         space = 8 + Vesting::LEN,
-        seeds = [b"vesting", token_mint.key().as_ref(), owner.key().as_ref()],
+        seeds = [b"vesting", token_mint.key().as_ref(), grantor.key().as_ref(), beneficiary.key().as_ref()],
         bump
     )]
     pub vesting: Account<'info, Vesting>,
@@ -285,16 +903,16 @@ pub struct InitializeVesting<'info> {
 #[derive(Accounts)]
 pub struct LockTokensForVesting<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub grantor: Signer<'info>,
 
     #[account(
         mut,
-        has_one = owner @ CustomError::UnauthorizedAccess
+        has_one = grantor @ CustomError::UnauthorizedAccess
     )]
     pub vesting: Account<'info, Vesting>,
 
     #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub grantor_token_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
     pub vesting_token_account: Account<'info, TokenAccount>,
@@ -302,21 +920,263 @@ pub struct LockTokensForVesting<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+/// Accounts the market cap of a launchpad token is read from.
+/// Vestings without a market cap condition only need the mint, the pool accounts are only
+/// needed once the bonding curve has migrated.
 #[derive(Accounts)]
-pub struct UnlockVestedTokens<'info> {
-    pub owner: Signer<'info>,
+pub struct MarketCapAccounts<'info> {
+    pub token_mint: Account<'info, Mint>,
 
-    #[account(
-        mut,
-        has_one = owner @ CustomError::UnauthorizedAccess
+    /// CHECK: Launchpad bonding curve, owner and PDA are validated when read
+    pub bonding_curve: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: dynamic_amm pool, validated against the bonding curve when read
+    pub pool: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Dynamic vault of pool token A, validated against the pool when read
+    pub a_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Dynamic vault of pool token B, validated against the pool when read
+    pub b_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pool's LP position in vault A, validated against the pool when read
+    pub a_vault_lp: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pool's LP position in vault B, validated against the pool when read
+    pub b_vault_lp: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: LP mint of vault A, validated against the vault when read
+    pub a_vault_lp_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: LP mint of vault B, validated against the vault when read
+    pub b_vault_lp_mint: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RecordMarketCap<'info> {
+    #[account(mut)]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = market.token_mint.key() == vesting.token_mint @ CustomError::InvalidMarketAccount)]
+    pub market: MarketCapAccounts<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary @ CustomError::UnauthorizedAccess
     )]
     pub vesting: Account<'info, Vesting>,
 
+    #[account(constraint = market.token_mint.key() == vesting.token_mint @ CustomError::InvalidMarketAccount)]
+    pub market: MarketCapAccounts<'info>,
+
+    #[account(mut)]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+
     #[account(mut)]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    pub grantor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = grantor @ CustomError::UnauthorizedAccess
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(
+        mut,
+        token::mint = vesting.token_mint,
+        token::authority = vesting
+    )]
     pub vesting_token_account: Account<'info, TokenAccount>,
 
+    #[account(mut, token::mint = vesting.token_mint)]
+    pub grantor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], num_recipients: u32)]
+pub struct CreateDistributor<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Distributor::space(num_recipients),
+        seeds = [b"distributor", token_mint.key().as_ref(), merkle_root.as_ref()],
+        bump
+    )]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_mint,
+        token::authority = distributor,
+        seeds = [b"distributor-tokens", distributor.key().as_ref()],
+        bump
+    )]
+    pub distributor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = token_mint)]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct ClaimDistribution<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    #[account(mut)]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        space = 8 + DistributionClaim::LEN,
+        seeds = [b"distribution-claim", distributor.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, DistributionClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"distributor-tokens", distributor.key().as_ref()],
+        bump
+    )]
+    pub distributor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = distributor.token_mint)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
     #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub funder: Signer<'info>,
+
+    /// CHECK: Only recorded as the account allowed to withdraw
+    pub recipient: UncheckedAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = funder,
+        space = 8 + Stream::LEN,
+        seeds = [b"stream", token_mint.key().as_ref(), funder.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        init,
+        payer = funder,
+        token::mint = token_mint,
+        token::authority = stream,
+        seeds = [b"stream-tokens", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = token_mint)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromStream<'info> {
+    pub recipient: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = recipient @ CustomError::UnauthorizedAccess
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"stream-tokens", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = stream.token_mint)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpStream<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = funder @ CustomError::UnauthorizedAccess,
+        constraint = !stream.is_cancelled @ CustomError::StreamCancelled
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"stream-tokens", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = stream.token_mint)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = funder @ CustomError::UnauthorizedAccess,
+        constraint = !stream.is_cancelled @ CustomError::StreamCancelled
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"stream-tokens", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = stream.token_mint)]
+    pub funder_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 }
@@ -329,18 +1189,255 @@ pub struct UnlockVestedTokens<'info> {
 pub struct Vault {
     pub owner: Pubkey,       // Who owns/created this vault
     pub bump: u8,            // PDA bump
-    pub locked_amount: u64,  // How many tokens currently locked
-    pub locked_until: i64,   // Unix timestamp until which tokens are locked
+    pub total_locked: u64,   // How many tokens are locked across all positions
+    pub next_nonce: u64,     // Nonce the next lock position is seeded with
+    pub positions: Vec<Pubkey>, // Open lock positions of the owner
 }
 
 impl Vault {
-    pub const LEN: usize = 32 + 1 + 8 + 8; 
-    // owner (32) + bump (1) + locked_amount (8) + locked_until (8)
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 4 + MAX_LOCK_POSITIONS * 32;
+    // owner (32) + bump (1) + total_locked (8) + next_nonce (8)
+    // + positions (4 + 32 * 32)
+}
+
+#[account]
+pub struct LockPosition {
+    pub owner: Pubkey,       // Who can unlock the position
+    pub vault: Pubkey,       // Vault whose token account holds the tokens
+    pub token_mint: Pubkey,  // Which token is locked
+    pub nonce: u64,          // Nonce the position PDA is seeded with
+    pub amount: u64,         // How many tokens are locked
+    pub locked_until: i64,   // Unix timestamp until which tokens are locked
+    pub lp_pool: Pubkey,     // Launchpad pool the locked LP tokens belong to, default for other tokens
+    pub bump: u8,            // PDA bump
+}
+
+impl LockPosition {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 32 + 1;
+    // owner (32) + vault (32) + token_mint (32) + nonce (8)
+    // + amount (8) + locked_until (8) + lp_pool (32) + bump (1)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InitializeVestingArgs {
+    pub amount: u64,             // Total amount vested
+    pub start_time: i64,         // When vesting begins, must be in the future
+    pub end_time: i64,           // When vesting ends (fully unlockable)
+    pub target_market_cap: u64,  // Extra condition: must exceed this market cap
+    pub schedule_type: VestingScheduleType, // How the amount is released over time
+    pub cliff_period: i64,       // Nothing is released before start_time + cliff_period
+    pub stages: u8,              // Number of releases for a staggered schedule
+    pub milestones: Vec<VestingMilestone>, // Release points for a milestone schedule
+    pub twap_window: i64,        // Seconds the market cap is averaged over, 0 without a market cap condition
+    pub market_cap_milestones: Vec<MarketCapMilestone>, // Market caps that unlock shares of the amount
+    pub revocable: bool,         // If the grantor can revoke what hasn't vested yet
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VestingScheduleType {
+    Linear,          // Released continuously between start_time and end_time
+    Cliff,           // Released all at once at end_time
+    Staggered,       // Released in `stages` equal steps between start_time and end_time
+    CustomMilestone, // Released by percentage at each milestone time
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingMilestone {
+    pub time: i64,       // Unix timestamp the milestone unlocks at
+    pub percentage: u8,  // Share of the vested amount, all milestones sum to 100
+}
+
+impl VestingMilestone {
+    pub const LEN: usize = 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MarketCapMilestone {
+    pub target_cap: u64,        // Market cap the milestone is reached at
+    pub unlock_percentage: u8,  // Share of the vested amount it unlocks, all milestones sum to 100
+    pub is_reached: bool,       // Latched once the market cap reaches target_cap
+}
+
+impl MarketCapMilestone {
+    pub const LEN: usize = 8 + 1 + 1;
+}
+
+/// Release schedule shared by vesting accounts and distributor claims.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingTerms {
+    pub schedule_type: VestingScheduleType, // How the amount is released over time
+    pub start_time: i64,                    // When vesting begins
+    pub end_time: i64,                      // When everything is vested
+    pub cliff_period: i64,                  // Nothing is released before start_time + cliff_period
+    pub stages: u8,                         // Number of releases for a staggered schedule
+    pub milestones: Vec<VestingMilestone>,  // Release points for a milestone schedule
+}
+
+impl VestingTerms {
+    // Schedule checks shared by vesting accounts and distributor leaves
+    pub fn validate(&self) -> Result<()> {
+        require!(self.end_time > self.start_time, CustomError::InvalidTimeParameters);
+
+        let vesting_duration = self.end_time
+            .checked_sub(self.start_time)
+            .ok_or(CustomError::CalculationError)?;
+        require!(
+            vesting_duration >= MINIMUM_VESTING_PERIOD 
+                && vesting_duration <= MAXIMUM_VESTING_PERIOD,
+            CustomError::InvalidTimeParameters
+        );
+        require!(
+            (0..=vesting_duration).contains(&self.cliff_period),
+            CustomError::InvalidTimeParameters
+        );
+
+        match self.schedule_type {
+            VestingScheduleType::Staggered => {
+                require!(self.stages > 0, CustomError::InvalidStages);
+            }
+            VestingScheduleType::CustomMilestone => {
+                require!(
+                    !self.milestones.is_empty() && self.milestones.len() <= MAX_MILESTONES,
+                    CustomError::InvalidMilestone
+                );
+                // Milestones are ordered and fall inside the vesting window
+                require!(
+                    self.milestones.windows(2).all(|pair| pair[0].time <= pair[1].time)
+                        && self.milestones.iter().all(|m| m.time >= self.start_time && m.time <= self.end_time),
+                    CustomError::InvalidMilestone
+                );
+                let total_percentage: u16 = self.milestones.iter()
+                    .map(|m| m.percentage as u16)
+                    .sum();
+                require!(total_percentage == 100, CustomError::InvalidMilestone);
+            }
+            VestingScheduleType::Linear | VestingScheduleType::Cliff => {}
+        }
+        Ok(())
+    }
+
+    // Part of `amount` vested at `current_time`
+    pub fn vested_amount(&self, amount: u64, current_time: i64) -> Result<u64> {
+        let cliff_end = self.start_time
+            .checked_add(self.cliff_period)
+            .ok_or(CustomError::CalculationError)?;
+        if current_time < cliff_end {
+            return Ok(0);
+        }
+        if current_time >= self.end_time {
+            return Ok(amount);
+        }
+
+        let total_duration = self.end_time
+            .checked_sub(self.start_time)
+            .ok_or(CustomError::CalculationError)?;
+        let elapsed_time = current_time
+            .checked_sub(self.start_time)
+            .ok_or(CustomError::CalculationError)?;
+
+        let vested_amount = match self.schedule_type {
+            VestingScheduleType::Linear => {
+                (amount as u128)
+                    .checked_mul(elapsed_time as u128)
+                    .ok_or(CustomError::CalculationError)?
+                    .checked_div(total_duration as u128)
+                    .ok_or(CustomError::CalculationError)?
+            }
+            VestingScheduleType::Cliff => 0,
+            VestingScheduleType::Staggered => {
+                let stages = self.stages as i64;
+                let stage_duration = total_duration
+                    .checked_div(stages)
+                    .filter(|duration| *duration > 0)
+                    .ok_or(CustomError::CalculationError)?;
+                let current_stage = (elapsed_time / stage_duration).min(stages);
+
+                (amount as u128)
+                    .checked_mul(current_stage as u128)
+                    .ok_or(CustomError::CalculationError)?
+                    .checked_div(stages as u128)
+                    .ok_or(CustomError::CalculationError)?
+            }
+            VestingScheduleType::CustomMilestone => {
+                let percentage: u128 = self.milestones.iter()
+                    .filter(|m| current_time >= m.time)
+                    .map(|m| m.percentage as u128)
+                    .sum();
+
+                (amount as u128)
+                    .checked_mul(percentage)
+                    .ok_or(CustomError::CalculationError)?
+                    .checked_div(100)
+                    .ok_or(CustomError::CalculationError)?
+            }
+        };
+
+        Ok((vested_amount as u64).min(amount))
+    }
+}
+
+/// Time-weighted market cap built from observations.
+/// Between two observations the lower of the two is counted, so a spike has to hold
+/// across observations to move the average.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MarketCapTwap {
+    pub window_start_time: i64,     // Start of the window being accumulated
+    pub cumulative_market_cap: u128, // Sum of market cap * seconds since window_start_time
+    pub last_observation_time: i64, // Time of the latest observation
+    pub last_market_cap: u64,       // Market cap at the latest observation
+    pub twap_market_cap: u64,       // Average of the last completed window
+    pub closed_window_duration: i64, // Length of the last completed window, 0 until one completes
+}
+
+impl MarketCapTwap {
+    pub const LEN: usize = 8 + 16 + 8 + 8 + 8 + 8;
+
+    // Average of the last completed window, None while the first window is still open
+    pub fn closed_twap_market_cap(&self) -> Option<u64> {
+        (self.closed_window_duration > 0).then_some(self.twap_market_cap)
+    }
+
+    // Observations closer together than MIN_OBSERVATION_INTERVAL can't be stacked to weight a price
+    pub fn accepts_observation(&self, current_time: i64) -> bool {
+        self.last_observation_time == 0
+            || current_time.saturating_sub(self.last_observation_time) >= MIN_OBSERVATION_INTERVAL
+    }
+
+    pub fn observe(&mut self, market_cap: u64, current_time: i64, window: i64) -> Result<()> {
+        require!(self.accepts_observation(current_time), CustomError::ObservationTooSoon);
+
+        if self.last_observation_time == 0 {
+            self.window_start_time = current_time;
+        } else if current_time > self.last_observation_time {
+            let elapsed = (current_time - self.last_observation_time) as u128;
+            self.cumulative_market_cap = self.cumulative_market_cap
+                .checked_add(
+                    (self.last_market_cap.min(market_cap) as u128)
+                        .checked_mul(elapsed)
+                        .ok_or(CustomError::CalculationError)?,
+                )
+                .ok_or(CustomError::CalculationError)?;
+        }
+        self.last_observation_time = current_time;
+        self.last_market_cap = market_cap;
+
+        // Close the window once it is long enough and start the next one
+        let window_duration = current_time - self.window_start_time;
+        if window_duration >= window && window_duration > 0 {
+            self.twap_market_cap = (self.cumulative_market_cap / window_duration as u128)
+                .min(u64::MAX as u128) as u64;
+            self.closed_window_duration = window_duration;
+            self.window_start_time = current_time;
+            self.cumulative_market_cap = 0;
+        }
+        Ok(())
+    }
 }
 
 #[account]
 pub struct Vesting {
-    pub owner: Pubkey,           // Who owns this vesting schedule
+    pub grantor: Pubkey,         // Who funds the vesting and can revoke it
+    pub beneficiary: Pubkey,     // Who claims the vested tokens
     pub token_mint: Pubkey,      // Which token is vested
     pub amount: u64,             // Total amount vested
     pub start_time: i64,         // When vesting *begins* 
@@ -348,13 +1445,214 @@ pub struct Vesting {
     pub target_market_cap: u64,  // Extra condition: must exceed this market cap
     pub is_locked: bool,         // If tokens are currently locked
     pub bump: u8,                // PDA bump
+    pub schedule_type: VestingScheduleType, // How the amount is released over time
+    pub cliff_period: i64,       // Nothing is released before start_time + cliff_period
+    pub stages: u8,              // Number of releases for a staggered schedule
+    pub released_amount: u64,    // Amount already claimed
+    pub twap_window: i64,        // Seconds the market cap is averaged over, 0 without a market cap condition
+    pub twap: MarketCapTwap,     // Market cap observations for the TWAP
+    pub milestones: Vec<VestingMilestone>, // Release points for a milestone schedule
+    pub revocable: bool,         // If the grantor can revoke what hasn't vested yet
+    pub revoked_at: i64,         // When the vesting was revoked, 0 if it wasn't
+    pub market_cap_milestones: Vec<MarketCapMilestone>, // Market caps that unlock shares of the amount
 }
 
 impl Vesting {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1;
-    // owner (32) + token_mint (32) + amount (8) 
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 8 + 1 + 8
+        + 8 + MarketCapTwap::LEN + 4 + MAX_MILESTONES * VestingMilestone::LEN
+        + 1 + 8 + 4 + MAX_MILESTONES * MarketCapMilestone::LEN;
+    // grantor (32) + beneficiary (32) + token_mint (32) + amount (8) 
     // + start_time (8) + end_time (8) + target_market_cap (8) 
-    // + is_locked (1) + bump (1)
+    // + is_locked (1) + bump (1) + schedule_type (1) + cliff_period (8)
+    // + stages (1) + released_amount (8) + twap_window (8)
+    // + twap (MarketCapTwap::LEN) + milestones (4 + 8 * 9)
+    // + revocable (1) + revoked_at (8) + market_cap_milestones (4 + 8 * 10)
+
+    // Amount the beneficiary ends up with, less than `amount` once revoked.
+    // A revoked vesting keeps what the market cap conditions had unlocked by then, and never less than was claimed.
+    pub fn final_vested_amount(&self) -> Result<u64> {
+        if self.revoked_at == 0 {
+            return self.calculate_vested_amount(i64::MAX);
+        }
+        Ok(self.claimable_vested_amount(i64::MAX)?.max(self.released_amount))
+    }
+
+    // Amount vested by `current_time` that the market cap conditions allow to be claimed
+    pub fn claimable_vested_amount(&self, current_time: i64) -> Result<u64> {
+        Ok(self.calculate_vested_amount(current_time)?
+            .min(self.market_cap_unlocked_amount()?))
+    }
+
+    // Whether a target, milestones or a TWAP make claims depend on the market cap
+    pub fn tracks_market_cap(&self) -> bool {
+        self.target_market_cap > 0 || self.twap_window > 0 || !self.market_cap_milestones.is_empty()
+    }
+
+    // Market cap the vesting conditions are checked against, the TWAP of the last completed window.
+    // None until a window completes, an observation too close to the previous one leaves the TWAP as it is.
+    pub fn observe_market_cap(&mut self, market_cap: u64, current_time: i64) -> Result<Option<u64>> {
+        if self.twap.accepts_observation(current_time) {
+            self.twap.observe(market_cap, current_time, self.twap_window)?;
+        }
+        Ok(self.twap.closed_twap_market_cap())
+    }
+
+    // Latches every milestone a completed TWAP window reaches, returns the newly reached ones with their index.
+    // Nothing latches before the first window completes.
+    pub fn reach_market_cap_milestones(&mut self, twap_market_cap: Option<u64>) -> Vec<(u8, MarketCapMilestone)> {
+        let Some(market_cap) = twap_market_cap else {
+            return vec![];
+        };
+        self.market_cap_milestones
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, m)| !m.is_reached && market_cap >= m.target_cap)
+            .map(|(index, m)| {
+                m.is_reached = true;
+                (index as u8, m.clone())
+            })
+            .collect()
+    }
+
+    // Amount the reached market cap milestones allow to be claimed. Without milestones a target
+    // unlocks all of it while the last completed TWAP window meets it, no condition unlocks all of it.
+    pub fn market_cap_unlocked_amount(&self) -> Result<u64> {
+        if self.market_cap_milestones.is_empty() {
+            let target_met = self.target_market_cap == 0
+                || matches!(self.twap.closed_twap_market_cap(), Some(cap) if cap >= self.target_market_cap);
+            return Ok(if target_met { self.amount } else { 0 });
+        }
+        let percentage: u128 = self.market_cap_milestones.iter()
+            .filter(|m| m.is_reached)
+            .map(|m| m.unlock_percentage as u128)
+            .sum();
+
+        Ok(((self.amount as u128)
+            .checked_mul(percentage)
+            .ok_or(CustomError::CalculationError)?
+            / 100)
+            .min(self.amount as u128) as u64)
+    }
+
+    // Total amount vested at `current_time`, claims release the difference to `released_amount`.
+    // Vesting stops at revocation.
+    pub fn calculate_vested_amount(&self, current_time: i64) -> Result<u64> {
+        let current_time = if self.revoked_at > 0 {
+            current_time.min(self.revoked_at)
+        } else {
+            current_time
+        };
+        self.terms().vested_amount(self.amount, current_time)
+    }
+
+    // Schedule the amount vests on
+    pub fn terms(&self) -> VestingTerms {
+        VestingTerms {
+            schedule_type: self.schedule_type,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            cliff_period: self.cliff_period,
+            stages: self.stages,
+            milestones: self.milestones.clone(),
+        }
+    }
+}
+
+#[account]
+pub struct Distributor {
+    pub authority: Pubkey,       // Who funded the distributor
+    pub token_mint: Pubkey,      // Which token is distributed
+    pub merkle_root: [u8; 32],   // Root over (index, recipient, amount, terms) leaves
+    pub total_amount: u64,       // Amount funded
+    pub claimed_amount: u64,     // Amount claimed across all recipients
+    pub num_recipients: u32,     // Number of leaves, the size of the bitmap
+    pub bump: u8,                // PDA bump
+    pub claimed_bitmap: Vec<u8>, // One bit per leaf, set once it is fully claimed
+}
+
+impl Distributor {
+    // authority (32) + token_mint (32) + merkle_root (32) + total_amount (8)
+    // + claimed_amount (8) + num_recipients (4) + bump (1)
+    // + claimed_bitmap (4 + 1 bit per recipient)
+    pub fn space(num_recipients: u32) -> usize {
+        32 + 32 + 32 + 8 + 8 + 4 + 1 + 4 + Self::bitmap_len(num_recipients)
+    }
+
+    pub fn bitmap_len(num_recipients: u32) -> usize {
+        (num_recipients as usize).div_ceil(8)
+    }
+
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed_bitmap[index as usize / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u32) {
+        self.claimed_bitmap[index as usize / 8] |= 1 << (index % 8);
+    }
+}
+
+#[account]
+pub struct DistributionClaim {
+    pub distributor: Pubkey,     // Distributor the leaf belongs to
+    pub recipient: Pubkey,       // Who claims the leaf
+    pub index: u32,              // Leaf index in the bitmap
+    pub released_amount: u64,    // Amount of the leaf already claimed
+    pub bump: u8,                // PDA bump
+}
+
+impl DistributionClaim {
+    pub const LEN: usize = 32 + 32 + 4 + 8 + 1;
+    // distributor (32) + recipient (32) + index (4)
+    // + released_amount (8) + bump (1)
+}
+
+#[account]
+pub struct Stream {
+    pub funder: Pubkey,          // Who deposits and can top up or cancel
+    pub recipient: Pubkey,       // Who withdraws what has accrued
+    pub token_mint: Pubkey,      // Which token is streamed
+    pub rate: u64,               // Tokens accrued per interval
+    pub interval: i64,           // Seconds between accruals
+    pub start_time: i64,         // When the stream starts accruing
+    pub last_update_time: i64,   // Accrual is settled up to this time
+    pub deposited_amount: u64,   // Total deposited, less what a cancel refunded
+    pub total_streamed: u64,     // Total accrued to the recipient
+    pub withdrawn_amount: u64,   // Amount the recipient already withdrew
+    pub is_cancelled: bool,      // Cancelled streams stop accruing
+    pub bump: u8,                // PDA bump
+}
+
+impl Stream {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+    // funder (32) + recipient (32) + token_mint (32) + rate (8)
+    // + interval (8) + start_time (8) + last_update_time (8)
+    // + deposited_amount (8) + total_streamed (8) + withdrawn_amount (8)
+    // + is_cancelled (1) + bump (1)
+
+    // Accrues every full interval since the last update, up to what is deposited
+    pub fn settle(&mut self, current_time: i64) -> Result<()> {
+        if self.is_cancelled || current_time <= self.last_update_time {
+            return Ok(());
+        }
+
+        let intervals = (current_time - self.last_update_time) / self.interval;
+        let remaining = self.deposited_amount
+            .checked_sub(self.total_streamed)
+            .ok_or(CustomError::CalculationError)?;
+        let accrued = (self.rate as u128)
+            .checked_mul(intervals as u128)
+            .ok_or(CustomError::CalculationError)?
+            .min(remaining as u128) as u64;
+        self.total_streamed += accrued;
+
+        // A dry stream restarts from now, otherwise the partial interval carries over
+        self.last_update_time = if self.total_streamed == self.deposited_amount {
+            current_time
+        } else {
+            self.last_update_time + intervals * self.interval
+        };
+        Ok(())
+    }
 }
 
 // -----------------
@@ -373,6 +1671,9 @@ pub struct TokensLocked {
     // vs. who *triggered* the lock (locker_authority).
     pub vault_owner: Pubkey,
     pub locker_authority: Pubkey,
+    pub lock_position: Pubkey,
+    pub nonce: u64,
+    pub lp_pool: Pubkey,
     pub amount: u64,
     pub lock_until: i64,
 }
@@ -381,13 +1682,32 @@ pub struct TokensLocked {
 pub struct TokensUnlocked {
     pub vault_owner: Pubkey,
     pub unlocker_authority: Pubkey,
+    pub lock_position: Pubkey,
     pub amount: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct VestingInitialized {
+pub struct LockExtended {
+    pub lock_position: Pubkey,
     pub owner: Pubkey,
+    pub previous_unlock_time: i64,
+    pub new_unlock_time: i64,
+}
+
+#[event]
+pub struct LockOwnershipTransferred {
+    pub lock_position: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub amount: u64,
+    pub locked_until: i64,
+}
+
+#[event]
+pub struct VestingInitialized {
+    pub grantor: Pubkey,
+    pub beneficiary: Pubkey,
     pub amount: u64,
     pub start_time: i64,
     pub end_time: i64,
@@ -395,15 +1715,120 @@ pub struct VestingInitialized {
 
 #[event]
 pub struct VestingTokensLocked {
-    pub owner: Pubkey,
+    pub grantor: Pubkey,
     pub amount: u64,
     pub vesting_account: Pubkey,
 }
 
 #[event]
-pub struct VestingTokensUnlocked {
-    pub owner: Pubkey,
+pub struct MarketCapRecorded {
+    pub vesting: Pubkey,
+    pub market_cap: u64,
+    pub twap_market_cap: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MilestoneReached {
+    pub vesting: Pubkey,
+    pub index: u8,
+    pub target_cap: u64,
+    pub unlock_percentage: u8,
+    pub market_cap: u64,
+    pub timestamp: i64,
+}
+
+fn emit_milestones_reached(
+    vesting: Pubkey,
+    reached: &[(u8, MarketCapMilestone)],
+    market_cap: u64,
+    timestamp: i64,
+) {
+    for (index, milestone) in reached {
+        emit!(MilestoneReached {
+            vesting,
+            index: *index,
+            target_cap: milestone.target_cap,
+            unlock_percentage: milestone.unlock_percentage,
+            market_cap,
+            timestamp,
+        });
+    }
+}
+
+#[event]
+pub struct VestedTokensClaimed {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub released_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub vesting: Pubkey,
+    pub grantor: Pubkey,
+    pub beneficiary: Pubkey,
+    pub vested_amount: u64,
+    pub refund: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributorCreated {
+    pub distributor: Pubkey,
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub num_recipients: u32,
+    pub total_amount: u64,
+}
+
+#[event]
+pub struct DistributionClaimed {
+    pub distributor: Pubkey,
+    pub recipient: Pubkey,
+    pub index: u32,
+    pub amount: u64,
+    pub released_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub stream: Pubkey,
+    pub funder: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub rate: u64,
+    pub interval: i64,
+    pub start_time: i64,
+}
+
+#[event]
+pub struct StreamWithdrawn {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
     pub amount: u64,
+    pub withdrawn_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamToppedUp {
+    pub stream: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub deposited_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamCancelled {
+    pub stream: Pubkey,
+    pub funder: Pubkey,
+    pub refund: u64,
+    pub total_streamed: u64,
     pub timestamp: i64,
 }
 
@@ -432,6 +1857,241 @@ pub enum CustomError {
     MarketCapNotReached,
     #[msg("Insufficient balance")]
     InsufficientBalance,
+    #[msg("Invalid milestone")]
+    InvalidMilestone,
+    #[msg("Invalid number of stages")]
+    InvalidStages,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Invalid market cap account")]
+    InvalidMarketAccount,
+    #[msg("Market cap is not tracked")]
+    MarketCapNotTracked,
+    #[msg("Invalid stream rate")]
+    InvalidStreamRate,
+    #[msg("Stream is cancelled")]
+    StreamCancelled,
+    #[msg("Too many lock positions")]
+    TooManyLockPositions,
+    #[msg("Unlock time can only be extended")]
+    InvalidUnlockTime,
+    #[msg("Vesting is not revocable")]
+    VestingNotRevocable,
+    #[msg("Vesting is revoked")]
+    VestingRevoked,
+    #[msg("Invalid number of recipients")]
+    InvalidRecipients,
+    #[msg("Already claimed")]
+    AlreadyClaimed,
+    #[msg("Invalid merkle proof")]
+    InvalidProof,
+    #[msg("Market cap conditions need a launchpad token that migrates to dynamic_amm")]
+    UnsupportedMarketCapSource,
+    #[msg("Market cap was observed too recently")]
+    ObservationTooSoon,
+}
+
+// ----------------------
+// Market Cap
+// ----------------------
+
+// Layout offsets of the accounts the market cap is read from, after the 8 byte discriminator
+const BONDING_CURVE_VIRTUAL_SOL_RESERVES: usize = 8;
+const BONDING_CURVE_VIRTUAL_TOKEN_RESERVES: usize = 16;
+const BONDING_CURVE_MIGRATED: usize = 98;
+const BONDING_CURVE_POOL: usize = 99;
+const BONDING_CURVE_MIGRATION_TARGET: usize = 131;
+const MIGRATION_TARGET_DYNAMIC_AMM: u8 = 0;
+const POOL_LP_MINT: usize = 8;
+const POOL_TOKEN_A_MINT: usize = 40;
+const POOL_TOKEN_B_MINT: usize = 72;
+const POOL_A_VAULT: usize = 104;
+const POOL_B_VAULT: usize = 136;
+const POOL_A_VAULT_LP: usize = 168;
+const POOL_B_VAULT_LP: usize = 200;
+const DYNAMIC_VAULT_TOTAL_AMOUNT: usize = 11;
+const DYNAMIC_VAULT_LP_MINT: usize = 115;
+const TOKEN_ACCOUNT_MINT: usize = 0;
+const TOKEN_ACCOUNT_AMOUNT: usize = 64;
+const MINT_SUPPLY: usize = 36;
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| error!(CustomError::InvalidMarketAccount))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    data.get(offset..offset + 32)
+        .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+        .ok_or_else(|| error!(CustomError::InvalidMarketAccount))
+}
+
+fn has_discriminator(data: &[u8], account_name: &str) -> bool {
+    let discriminator = hash(format!("account:{}", account_name).as_bytes()).to_bytes();
+    data.len() >= 8 && data[..8] == discriminator[..8]
+}
+
+// Reads an account that must be present and owned by `owner`
+fn read_account<'a, 'info>(
+    account: &'a Option<UncheckedAccount<'info>>,
+    owner: &Pubkey,
+) -> Result<&'a UncheckedAccount<'info>> {
+    let account = account.as_ref().ok_or(CustomError::InvalidMarketAccount)?;
+    require_keys_eq!(*account.owner, *owner, CustomError::InvalidMarketAccount);
+    Ok(account)
+}
+
+// The launchpad bonding curve PDA of `token_mint`
+fn require_launchpad_curve(bonding_curve: &AccountInfo, token_mint: &Pubkey) -> Result<()> {
+    require_keys_eq!(*bonding_curve.owner, launchpad::ID, CustomError::InvalidMarketAccount);
+    let (bonding_curve_key, _) = Pubkey::find_program_address(
+        &[b"bonding-curve", token_mint.as_ref()],
+        &launchpad::ID,
+    );
+    require_keys_eq!(bonding_curve.key(), bonding_curve_key, CustomError::InvalidMarketAccount);
+
+    let curve_data = bonding_curve.try_borrow_data()?;
+    require!(has_discriminator(&curve_data, "BondingCurve"), CustomError::InvalidMarketAccount);
+    Ok(())
+}
+
+// Market cap is price * supply, with price = sol_reserves / token_reserves
+pub fn market_cap_from_reserves(sol_reserves: u64, token_reserves: u64, supply: u64) -> Result<u64> {
+    require!(token_reserves > 0, CustomError::InvalidMarketAccount);
+    let market_cap = (sol_reserves as u128)
+        .checked_mul(supply as u128)
+        .ok_or(CustomError::CalculationError)?
+        / token_reserves as u128;
+    Ok(market_cap.min(u64::MAX as u128) as u64)
+}
+
+// Market cap of a launchpad curve that hasn't migrated. Supply curves quote their virtual reserves
+// at the spot price, a completed one over a fixed token amount until it migrates
+pub fn bonding_curve_market_cap(curve_data: &[u8], supply: u64) -> Result<u64> {
+    market_cap_from_reserves(
+        read_u64(curve_data, BONDING_CURVE_VIRTUAL_SOL_RESERVES)?,
+        read_u64(curve_data, BONDING_CURVE_VIRTUAL_TOKEN_RESERVES)?,
+        supply,
+    )
+}
+
+// Tokens a pool owns in a dynamic vault, its LP share of the vault total
+pub fn vault_share(total_amount: u64, lp_amount: u64, lp_supply: u64) -> Result<u64> {
+    require!(lp_supply > 0, CustomError::InvalidMarketAccount);
+    Ok(((total_amount as u128)
+        .checked_mul(lp_amount as u128)
+        .ok_or(CustomError::CalculationError)?
+        / lp_supply as u128) as u64)
+}
+
+// LP mint of the pool a launchpad bonding curve migrated into
+pub fn launchpad_pool_lp_mint(bonding_curve: &AccountInfo, pool: &AccountInfo) -> Result<Pubkey> {
+    require_keys_eq!(*bonding_curve.owner, launchpad::ID, CustomError::InvalidMarketAccount);
+    require_keys_eq!(*pool.owner, dynamic_amm::ID, CustomError::InvalidMarketAccount);
+
+    let curve_data = bonding_curve.try_borrow_data()?;
+    require!(has_discriminator(&curve_data, "BondingCurve"), CustomError::InvalidMarketAccount);
+    let migrated = *curve_data.get(BONDING_CURVE_MIGRATED).ok_or(CustomError::InvalidMarketAccount)? != 0;
+    require!(migrated, CustomError::InvalidMarketAccount);
+    require_keys_eq!(pool.key(), read_pubkey(&curve_data, BONDING_CURVE_POOL)?, CustomError::InvalidMarketAccount);
+
+    let pool_data = pool.try_borrow_data()?;
+    require!(has_discriminator(&pool_data, "Pool"), CustomError::InvalidMarketAccount);
+
+    // The curve is the PDA of one of the pool's mints
+    let is_pool_curve = [POOL_TOKEN_A_MINT, POOL_TOKEN_B_MINT].iter().any(|offset| {
+        read_pubkey(&pool_data, *offset).is_ok_and(|mint| {
+            Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &launchpad::ID).0
+                == bonding_curve.key()
+        })
+    });
+    require!(is_pool_curve, CustomError::InvalidMarketAccount);
+
+    read_pubkey(&pool_data, POOL_LP_MINT)
+}
+
+impl<'info> MarketCapAccounts<'info> {
+    // Market cap in lamports, from the bonding curve while it is live and from the pool once migrated
+    pub fn market_cap(&self) -> Result<u64> {
+        let token_mint = self.token_mint.key();
+        let supply = self.token_mint.supply;
+
+        let bonding_curve = read_account(&self.bonding_curve, &launchpad::ID)?;
+        require_launchpad_curve(bonding_curve, &token_mint)?;
+        let curve_data = bonding_curve.try_borrow_data()?;
+
+        let migrated = *curve_data.get(BONDING_CURVE_MIGRATED).ok_or(CustomError::InvalidMarketAccount)? != 0;
+        if !migrated {
+            return bonding_curve_market_cap(&curve_data, supply);
+        }
+
+        // After migration the pool the curve recorded is the only one accepted
+        let pool = read_account(&self.pool, &dynamic_amm::ID)?;
+        require_keys_eq!(pool.key(), read_pubkey(&curve_data, BONDING_CURVE_POOL)?, CustomError::InvalidMarketAccount);
+        let pool_data = pool.try_borrow_data()?;
+        require!(has_discriminator(&pool_data, "Pool"), CustomError::InvalidMarketAccount);
+
+        let a_reserves = self.pool_reserves(
+            &pool_data,
+            (&self.a_vault, POOL_A_VAULT),
+            (&self.a_vault_lp, POOL_A_VAULT_LP),
+            &self.a_vault_lp_mint,
+        )?;
+        let b_reserves = self.pool_reserves(
+            &pool_data,
+            (&self.b_vault, POOL_B_VAULT),
+            (&self.b_vault_lp, POOL_B_VAULT_LP),
+            &self.b_vault_lp_mint,
+        )?;
+
+        let (token_reserves, sol_reserves) = if read_pubkey(&pool_data, POOL_TOKEN_A_MINT)? == token_mint {
+            (a_reserves, b_reserves)
+        } else {
+            (b_reserves, a_reserves)
+        };
+        market_cap_from_reserves(sol_reserves, token_reserves, supply)
+    }
+
+    // One side of the pool, read through the dynamic vault the pool deposits into
+    fn pool_reserves(
+        &self,
+        pool_data: &[u8],
+        (vault, vault_offset): (&Option<UncheckedAccount<'info>>, usize),
+        (vault_lp, vault_lp_offset): (&Option<UncheckedAccount<'info>>, usize),
+        vault_lp_mint: &Option<UncheckedAccount<'info>>,
+    ) -> Result<u64> {
+        let vault = read_account(vault, &dynamic_vault::ID)?;
+        require_keys_eq!(vault.key(), read_pubkey(pool_data, vault_offset)?, CustomError::InvalidMarketAccount);
+        let vault_data = vault.try_borrow_data()?;
+        require!(has_discriminator(&vault_data, "Vault"), CustomError::InvalidMarketAccount);
+        let lp_mint_key = read_pubkey(&vault_data, DYNAMIC_VAULT_LP_MINT)?;
+
+        let vault_lp = read_account(vault_lp, &token::ID)?;
+        require_keys_eq!(vault_lp.key(), read_pubkey(pool_data, vault_lp_offset)?, CustomError::InvalidMarketAccount);
+        let vault_lp_data = vault_lp.try_borrow_data()?;
+        require_keys_eq!(read_pubkey(&vault_lp_data, TOKEN_ACCOUNT_MINT)?, lp_mint_key, CustomError::InvalidMarketAccount);
+
+        let vault_lp_mint = read_account(vault_lp_mint, &token::ID)?;
+        require_keys_eq!(vault_lp_mint.key(), lp_mint_key, CustomError::InvalidMarketAccount);
+        let vault_lp_mint_data = vault_lp_mint.try_borrow_data()?;
+
+        vault_share(
+            read_u64(&vault_data, DYNAMIC_VAULT_TOTAL_AMOUNT)?,
+            read_u64(&vault_lp_data, TOKEN_ACCOUNT_AMOUNT)?,
+            read_u64(&vault_lp_mint_data, MINT_SUPPLY)?,
+        )
+    }
+}
+
+// ----------------------
+// Merkle Distribution
+// ----------------------
+
+// Leaf of a distributor tree, the terms are hashed in their serialized form
+pub fn distribution_leaf(index: u32, recipient: &Pubkey, amount: u64, terms: &VestingTerms) -> Result<[u8; 32]> {
+    let terms = terms.try_to_vec()?;
+    Ok(hashv(&[&index.to_le_bytes(), recipient.as_ref(), &amount.to_le_bytes(), &terms]).to_bytes())
 }
 
 // ----------------------
@@ -439,6 +2099,50 @@ pub enum CustomError {
 // ----------------------
 
 impl<'info> LockTokens<'info> {
+    // Moves the tokens into the vault and records them as a new position
+    fn open_position(&mut self, amount: u64, lock_duration: i64, bump: u8, lp_pool: Pubkey) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let unlock_time = current_time
+            .checked_add(lock_duration)
+            .ok_or(CustomError::CalculationError)?;
+
+        // Transfer from the user's token account into the vault's token account.
+        token::transfer(self.into_transfer_to_vault_context(), amount)?;
+
+        // Every lock is its own position, seeded by the vault's next nonce
+        let vault = &mut self.vault;
+        require!(vault.positions.len() < MAX_LOCK_POSITIONS, CustomError::TooManyLockPositions);
+
+        let lock_position = &mut self.lock_position;
+        lock_position.owner = vault.owner;
+        lock_position.vault = vault.key();
+        lock_position.token_mint = self.user_token_account.mint;
+        lock_position.nonce = vault.next_nonce;
+        lock_position.amount = amount;
+        lock_position.locked_until = unlock_time;
+        lock_position.lp_pool = lp_pool;
+        lock_position.bump = bump;
+
+        vault.positions.push(lock_position.key());
+        vault.next_nonce = vault.next_nonce
+            .checked_add(1)
+            .ok_or(CustomError::CalculationError)?;
+        vault.total_locked = vault.total_locked
+            .checked_add(amount)
+            .ok_or(CustomError::CalculationError)?;
+
+        emit!(TokensLocked {
+            vault_owner: vault.owner,
+            locker_authority: self.authority.key(),
+            lock_position: lock_position.key(),
+            nonce: lock_position.nonce,
+            lp_pool,
+            amount,
+            lock_until: unlock_time,
+        });
+        Ok(())
+    }
+
     // Transfers from the user’s token account -> vault token account
     fn into_transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
@@ -451,13 +2155,459 @@ impl<'info> LockTokens<'info> {
 }
 
 impl<'info> LockTokensForVesting<'info> {
-    // Transfers from the user’s token account -> vesting PDA token account
+    // Transfers from the grantor’s token account -> vesting PDA token account
     fn into_transfer_to_vesting_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
-            from: self.owner_token_account.to_account_info(),
+            from: self.grantor_token_account.to_account_info(),
             to: self.vesting_token_account.to_account_info(),
-            authority: self.owner.to_account_info(), // user
+            authority: self.grantor.to_account_info(), // grantor
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+impl<'info> CreateDistributor<'info> {
+    // Transfers from the authority's token account -> distributor PDA token account
+    fn into_transfer_to_distributor_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.authority_token_account.to_account_info(),
+            to: self.distributor_token_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+impl<'info> CreateStream<'info> {
+    // Transfers from the funder's token account -> stream PDA token account
+    fn into_transfer_to_stream_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.funder_token_account.to_account_info(),
+            to: self.stream_token_account.to_account_info(),
+            authority: self.funder.to_account_info(),
         };
         CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
     }
 }
+
+impl<'info> TopUpStream<'info> {
+    // Transfers from the funder's token account -> stream PDA token account
+    fn into_transfer_to_stream_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.funder_token_account.to_account_info(),
+            to: self.stream_token_account.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+// Transfers out of the stream PDA token account, signed with the stream seeds
+fn transfer_from_stream<'info>(
+    stream: &Account<'info, Stream>,
+    stream_token_account: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"stream",
+        stream.token_mint.as_ref(),
+        stream.funder.as_ref(),
+        stream.recipient.as_ref(),
+        &[stream.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: stream_token_account.to_account_info(),
+        to: to.to_account_info(),
+        authority: stream.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        amount,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+    const DURATION: i64 = SECONDS_IN_DAY * 100;
+
+    fn vesting(schedule_type: VestingScheduleType) -> Vesting {
+        Vesting {
+            grantor: Pubkey::default(),
+            beneficiary: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            amount: 1_000_000,
+            start_time: START,
+            end_time: START + DURATION,
+            target_market_cap: 0,
+            is_locked: true,
+            bump: 255,
+            schedule_type,
+            cliff_period: 0,
+            stages: 0,
+            released_amount: 0,
+            twap_window: 0,
+            twap: MarketCapTwap::default(),
+            milestones: vec![],
+            revocable: true,
+            revoked_at: 0,
+            market_cap_milestones: vec![],
+        }
+    }
+
+    // Vested amounts at each offset from the start
+    fn vested_at(vesting: &Vesting, offsets: &[i64]) -> Vec<u64> {
+        offsets
+            .iter()
+            .map(|offset| vesting.calculate_vested_amount(START + offset).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_linear() {
+        let vesting = vesting(VestingScheduleType::Linear);
+        assert_eq!(
+            vested_at(&vesting, &[-1, 0, DURATION / 4, DURATION / 2, DURATION - 1, DURATION, DURATION * 2]),
+            vec![0, 0, 250_000, 500_000, 999_999, 1_000_000, 1_000_000]
+        );
+    }
+
+    #[test]
+    fn test_linear_with_cliff() {
+        let mut vesting = vesting(VestingScheduleType::Linear);
+        vesting.cliff_period = DURATION / 4;
+        assert_eq!(
+            vested_at(&vesting, &[DURATION / 4 - 1, DURATION / 4, DURATION / 2, DURATION]),
+            vec![0, 250_000, 500_000, 1_000_000]
+        );
+    }
+
+    #[test]
+    fn test_cliff() {
+        let vesting = vesting(VestingScheduleType::Cliff);
+        assert_eq!(
+            vested_at(&vesting, &[0, DURATION / 2, DURATION - 1, DURATION]),
+            vec![0, 0, 0, 1_000_000]
+        );
+    }
+
+    #[test]
+    fn test_staggered() {
+        let mut vesting = vesting(VestingScheduleType::Staggered);
+        vesting.stages = 5;
+        let stage = DURATION / 5;
+        assert_eq!(
+            vested_at(&vesting, &[0, stage - 1, stage, stage * 2, stage * 4 + 1, DURATION]),
+            vec![0, 0, 200_000, 400_000, 800_000, 1_000_000]
+        );
+
+        // Stage amounts that don't divide evenly still end at the full amount
+        vesting.stages = 3;
+        vesting.amount = 1_000;
+        assert_eq!(
+            vested_at(&vesting, &[DURATION / 3, DURATION / 3 * 2, DURATION]),
+            vec![333, 666, 1_000]
+        );
+    }
+
+    #[test]
+    fn test_custom_milestone() {
+        let mut vesting = vesting(VestingScheduleType::CustomMilestone);
+        vesting.milestones = vec![
+            VestingMilestone { time: START + SECONDS_IN_DAY * 10, percentage: 10 },
+            VestingMilestone { time: START + SECONDS_IN_DAY * 50, percentage: 40 },
+            VestingMilestone { time: START + DURATION, percentage: 50 },
+        ];
+        assert_eq!(
+            vested_at(&vesting, &[0, SECONDS_IN_DAY * 10 - 1, SECONDS_IN_DAY * 10, SECONDS_IN_DAY * 50, DURATION]),
+            vec![0, 0, 100_000, 500_000, 1_000_000]
+        );
+    }
+
+    #[test]
+    fn test_market_cap_from_reserves() {
+        // 30 SOL against 1.073B tokens with 1B supply
+        assert_eq!(
+            market_cap_from_reserves(30_000_000_000, 1_073_000_000_000_000, 1_000_000_000_000_000).unwrap(),
+            27_958_993_476
+        );
+        assert_eq!(market_cap_from_reserves(u64::MAX, 1, u64::MAX).unwrap(), u64::MAX);
+        assert!(market_cap_from_reserves(1, 0, 1).is_err());
+
+        // Half the vault LP owns half the vault
+        assert_eq!(vault_share(1_000, 50, 100).unwrap(), 500);
+        assert!(vault_share(1_000, 50, 0).is_err());
+    }
+
+    #[test]
+    fn test_completed_unmigrated_curve_market_cap() {
+        // A sold out supply curve awaiting migration, real reserves are empty and the virtual
+        // reserves quote its final price of 150 lamports per 1M base units over 1e12 tokens
+        let mut curve_data = vec![0u8; BONDING_CURVE_POOL + 32];
+        curve_data[..8].copy_from_slice(&hash(b"account:BondingCurve").to_bytes()[..8]);
+        curve_data[BONDING_CURVE_VIRTUAL_SOL_RESERVES..][..8].copy_from_slice(&150_000_000u64.to_le_bytes());
+        curve_data[BONDING_CURVE_VIRTUAL_TOKEN_RESERVES..][..8].copy_from_slice(&1_000_000_000_000u64.to_le_bytes());
+        assert!(has_discriminator(&curve_data, "BondingCurve"));
+        assert_eq!(curve_data[BONDING_CURVE_MIGRATED], 0);
+
+        assert_eq!(bonding_curve_market_cap(&curve_data, 1_000_000_000_000_000).unwrap(), 150_000_000_000);
+
+        // Without quoted reserves there is no price to read
+        curve_data[BONDING_CURVE_VIRTUAL_TOKEN_RESERVES..][..8].copy_from_slice(&0u64.to_le_bytes());
+        assert!(bonding_curve_market_cap(&curve_data, 1_000_000_000_000_000).is_err());
+    }
+
+    #[test]
+    fn test_tracks_market_cap() {
+        // A plain time schedule never reads the market cap
+        let plain = vesting(VestingScheduleType::Linear);
+        assert!(!plain.tracks_market_cap());
+        assert_eq!(plain.market_cap_unlocked_amount().unwrap(), plain.amount);
+
+        let mut target = vesting(VestingScheduleType::Linear);
+        target.target_market_cap = 1;
+        assert!(target.tracks_market_cap());
+
+        let mut twap = vesting(VestingScheduleType::Linear);
+        twap.twap_window = 600;
+        assert!(twap.tracks_market_cap());
+
+        let mut milestones = vesting(VestingScheduleType::Linear);
+        milestones.market_cap_milestones = vec![MarketCapMilestone { target_cap: 100, unlock_percentage: 100, is_reached: false }];
+        assert!(milestones.tracks_market_cap());
+    }
+
+    #[test]
+    fn test_twap_needs_a_full_window() {
+        let window = 600;
+        let mut twap = MarketCapTwap::default();
+
+        twap.observe(100, START, window).unwrap();
+        twap.observe(100, START + 300, window).unwrap();
+        assert_eq!(twap.closed_twap_market_cap(), None);
+
+        twap.observe(100, START + 600, window).unwrap();
+        assert_eq!(twap.closed_twap_market_cap(), Some(100));
+        assert_eq!(twap.window_start_time, START + 600);
+    }
+
+    #[test]
+    fn test_twap_ignores_a_single_spike() {
+        let window = 600;
+        let mut twap = MarketCapTwap::default();
+
+        // A manipulated observation counts at the lower of its neighbours
+        twap.observe(100, START, window).unwrap();
+        twap.observe(1_000_000, START + 299, window).unwrap();
+        twap.observe(100, START + 600, window).unwrap();
+        assert_eq!(twap.twap_market_cap, 100);
+
+        // A market cap that holds across observations moves the average
+        twap.observe(300, START + 900, window).unwrap();
+        twap.observe(300, START + 1_200, window).unwrap();
+        assert_eq!(twap.twap_market_cap, 200);
+    }
+
+    #[test]
+    fn test_twap_rejects_stacked_observations() {
+        let window = 600;
+        let mut twap = MarketCapTwap::default();
+
+        twap.observe(100, START, window).unwrap();
+        assert!(!twap.accepts_observation(START + MIN_OBSERVATION_INTERVAL - 1));
+        assert!(twap.observe(1_000_000, START + MIN_OBSERVATION_INTERVAL - 1, window).is_err());
+        assert_eq!(twap.last_market_cap, 100);
+
+        twap.observe(100, START + MIN_OBSERVATION_INTERVAL, window).unwrap();
+        assert_eq!(twap.last_observation_time, START + MIN_OBSERVATION_INTERVAL);
+    }
+
+    #[test]
+    fn test_market_cap_milestones_latch() {
+        let mut vesting = vesting(VestingScheduleType::Linear);
+        vesting.market_cap_milestones = [(100, 25), (200, 25), (400, 50)]
+            .into_iter()
+            .map(|(target_cap, unlock_percentage)| MarketCapMilestone {
+                target_cap,
+                unlock_percentage,
+                is_reached: false,
+            })
+            .collect();
+        assert_eq!(vesting.market_cap_unlocked_amount().unwrap(), 0);
+
+        // Nothing latches before a TWAP window completes
+        assert!(vesting.reach_market_cap_milestones(None).is_empty());
+
+        let reached = vesting.reach_market_cap_milestones(Some(250));
+        assert_eq!(reached.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(vesting.market_cap_unlocked_amount().unwrap(), 500_000);
+
+        // A drop in market cap keeps reached milestones, reaching them again emits nothing
+        assert!(vesting.reach_market_cap_milestones(Some(50)).is_empty());
+        assert!(vesting.reach_market_cap_milestones(Some(250)).is_empty());
+        assert_eq!(vesting.market_cap_unlocked_amount().unwrap(), 500_000);
+
+        assert_eq!(vesting.reach_market_cap_milestones(Some(400)).len(), 1);
+        assert_eq!(vesting.market_cap_unlocked_amount().unwrap(), vesting.amount);
+    }
+
+    #[test]
+    fn test_revoked_vesting_stops_vesting() {
+        let mut vesting = vesting(VestingScheduleType::Linear);
+        vesting.revoked_at = START + DURATION / 4;
+
+        assert_eq!(vested_at(&vesting, &[DURATION / 10, DURATION / 4, DURATION]), vec![100_000, 250_000, 250_000]);
+        assert_eq!(vesting.final_vested_amount().unwrap(), 250_000);
+
+        // Revoked before the cliff nothing vests
+        vesting.cliff_period = DURATION / 2;
+        assert_eq!(vesting.final_vested_amount().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_revoked_vesting_keeps_market_cap_gate() {
+        let mut milestones = vesting(VestingScheduleType::Linear);
+        milestones.market_cap_milestones = [(100, 25), (200, 75)]
+            .into_iter()
+            .map(|(target_cap, unlock_percentage)| MarketCapMilestone {
+                target_cap,
+                unlock_percentage,
+                is_reached: false,
+            })
+            .collect();
+        milestones.reach_market_cap_milestones(Some(100));
+        milestones.revoked_at = START + DURATION / 2;
+
+        // Half vested by time, only the first milestone's quarter was unlocked
+        assert_eq!(milestones.final_vested_amount().unwrap(), 250_000);
+
+        // An unmet target unlocks nothing, what was already claimed stays the beneficiary's
+        let mut target = vesting(VestingScheduleType::Linear);
+        target.target_market_cap = 100;
+        target.revoked_at = START + DURATION / 2;
+        assert_eq!(target.final_vested_amount().unwrap(), 0);
+        target.released_amount = 100_000;
+        assert_eq!(target.final_vested_amount().unwrap(), 100_000);
+    }
+
+    #[test]
+    fn test_partial_claims_release_the_difference() {
+        let mut vesting = vesting(VestingScheduleType::Linear);
+        let mut claimed = 0;
+        for offset in [DURATION / 10, DURATION / 2, DURATION / 2, DURATION] {
+            let vested = vesting.calculate_vested_amount(START + offset).unwrap();
+            claimed += vested - vesting.released_amount;
+            vesting.released_amount = vested;
+        }
+        assert_eq!(claimed, vesting.amount);
+    }
+
+    #[test]
+    fn test_distribution_proof_and_bitmap() {
+        let terms = vesting(VestingScheduleType::Linear).terms();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let alice_leaf = distribution_leaf(0, &alice, 1_000, &terms).unwrap();
+        let bob_leaf = distribution_leaf(1, &bob, 2_000, &terms).unwrap();
+        let root = if alice_leaf <= bob_leaf {
+            hashv(&[&alice_leaf, &bob_leaf]).to_bytes()
+        } else {
+            hashv(&[&bob_leaf, &alice_leaf]).to_bytes()
+        };
+
+        assert!(verify_merkle_proof(&[bob_leaf], &root, alice_leaf));
+        assert!(verify_merkle_proof(&[alice_leaf], &root, bob_leaf));
+        // A leaf with a different amount or recipient doesn't verify
+        assert!(!verify_merkle_proof(&[bob_leaf], &root, distribution_leaf(0, &alice, 2_000, &terms).unwrap()));
+        assert!(!verify_merkle_proof(&[bob_leaf], &root, distribution_leaf(0, &bob, 1_000, &terms).unwrap()));
+
+        // Leaves are time-released on the same schedule as a vesting account
+        assert_eq!(terms.vested_amount(1_000, START + DURATION / 2).unwrap(), 500);
+
+        // and can't carry terms a vesting account would reject
+        assert!(terms.validate().is_ok());
+        let mut staggered = vesting(VestingScheduleType::Staggered).terms();
+        assert!(staggered.validate().is_err());
+        staggered.stages = 4;
+        assert!(staggered.validate().is_ok());
+
+        let mut distributor = Distributor {
+            authority: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            merkle_root: root,
+            total_amount: 3_000,
+            claimed_amount: 0,
+            num_recipients: 9,
+            bump: 255,
+            claimed_bitmap: vec![0; Distributor::bitmap_len(9)],
+        };
+        assert_eq!(distributor.claimed_bitmap.len(), 2);
+        distributor.set_claimed(8);
+        assert!(distributor.is_claimed(8));
+        assert!(!distributor.is_claimed(0));
+    }
+
+    fn stream(deposited_amount: u64) -> Stream {
+        Stream {
+            funder: Pubkey::default(),
+            recipient: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            rate: 10,
+            interval: 60,
+            start_time: START,
+            last_update_time: START,
+            deposited_amount,
+            total_streamed: 0,
+            withdrawn_amount: 0,
+            is_cancelled: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_stream_accrues_full_intervals() {
+        let mut stream = stream(1_000);
+
+        stream.settle(START - 60).unwrap();
+        assert_eq!(stream.total_streamed, 0);
+
+        stream.settle(START + 90).unwrap();
+        assert_eq!(stream.total_streamed, 10);
+        assert_eq!(stream.last_update_time, START + 60);
+
+        // The partial interval carries over to the next settle
+        stream.settle(START + 120).unwrap();
+        assert_eq!(stream.total_streamed, 20);
+    }
+
+    #[test]
+    fn test_stream_top_up_after_running_dry() {
+        let mut stream = stream(100);
+
+        stream.settle(START + 60 * 100).unwrap();
+        assert_eq!(stream.total_streamed, 100);
+
+        // Topping up resumes from the top up, the empty time isn't paid out
+        stream.deposited_amount += 100;
+        stream.settle(START + 60 * 101).unwrap();
+        assert_eq!(stream.total_streamed, 110);
+    }
+
+    #[test]
+    fn test_cancelled_stream_stops_accruing() {
+        let mut stream = stream(1_000);
+
+        stream.settle(START + 60 * 5).unwrap();
+        stream.deposited_amount = stream.total_streamed;
+        stream.is_cancelled = true;
+
+        stream.settle(START + 60 * 50).unwrap();
+        assert_eq!(stream.total_streamed, 50);
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::amm::{BondingCurveModel, CurveReserves, PRICE_SCALE};
+
 #[derive(Debug)]
 pub struct BuyResult {
     pub token_amount: u64,
@@ -12,7 +14,7 @@ pub struct SellResult {
     pub sol_amount: u64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AMM {
     pub virtual_sol_reserves: u128,
     pub virtual_token_reserves: u128,
@@ -51,6 +53,33 @@ impl AMM {
         Some(amount_needed)
     }
 
+    // inverse of get_buy_price: the most tokens whose price plus fee fits in sol_amount
+    pub fn get_tokens_for_sol(&self, sol_amount: u128, fee_basis_points: u128) -> Option<u128> {
+        let mut net_sol_amount = sol_amount.checked_mul(10000)?.checked_div(fee_basis_points.checked_add(10000)?)?;
+
+        // the division above rounds down, so one more lamport of price can still fit
+        let next_sol_amount = net_sol_amount.checked_add(1)?;
+        let next_fee = next_sol_amount.checked_mul(fee_basis_points)?.checked_div(10000)?;
+        if next_sol_amount.checked_add(next_fee)? <= sol_amount {
+            net_sol_amount = next_sol_amount;
+        }
+
+        if net_sol_amount == 0 {
+            return None;
+        }
+
+        let product_of_reserves = self.virtual_sol_reserves.checked_mul(self.virtual_token_reserves)?;
+        let new_virtual_sol_reserves = self.virtual_sol_reserves.checked_add(net_sol_amount)?;
+        let new_virtual_token_reserves = product_of_reserves.checked_div(new_virtual_sol_reserves)?.checked_add(1)?;
+        let tokens = self.virtual_token_reserves.checked_sub(new_virtual_token_reserves)?;
+
+        if tokens == 0 {
+            return None;
+        }
+
+        Some(tokens)
+    }
+
     pub fn apply_buy(&mut self, token_amount: u128) -> Option<BuyResult> {
         let final_token_amount = if token_amount > self.real_token_reserves {
             self.real_token_reserves
@@ -100,6 +129,92 @@ impl AMM {
 
         Some(sol_received.min(self.real_sol_reserves))
     }
+
+    // inverse of apply_sell: the fewest tokens whose sell price minus fee is at least sol_amount
+    pub fn get_tokens_for_sol_out(&self, sol_amount: u128, fee_basis_points: u128) -> Option<u128> {
+        if sol_amount == 0 || fee_basis_points >= 10000 {
+            return None;
+        }
+
+        // round the gross amount up, then walk it back down while the net still covers sol_amount
+        let fee_denominator = 10000 - fee_basis_points;
+        let mut gross_sol_amount = sol_amount.checked_mul(10000)?.checked_add(fee_denominator - 1)?.checked_div(fee_denominator)?;
+        loop {
+            let prev_sol_amount = gross_sol_amount.checked_sub(1)?;
+            let prev_fee = prev_sol_amount.checked_mul(fee_basis_points)?.checked_div(10000)?;
+            if prev_sol_amount.checked_sub(prev_fee)? < sol_amount {
+                break;
+            }
+            gross_sol_amount = prev_sol_amount;
+        }
+
+        if gross_sol_amount > self.real_sol_reserves {
+            return None;
+        }
+
+        // get_sell_price pays virtual_sol_reserves * proportion / scaling_factor, so the
+        // proportion has to be rounded up for the payout to reach gross_sol_amount
+        let scaling_factor = self.initial_virtual_token_reserves;
+        let token_sell_proportion = gross_sol_amount
+            .checked_mul(scaling_factor)?
+            .checked_add(self.virtual_sol_reserves.checked_sub(1)?)?
+            .checked_div(self.virtual_sol_reserves)?;
+        if token_sell_proportion >= scaling_factor {
+            return None;
+        }
+
+        // proportion = tokens * scaling_factor / (virtual_token_reserves + tokens), solved for tokens and rounded up
+        let numerator = token_sell_proportion.checked_mul(self.virtual_token_reserves)?;
+        let denominator = scaling_factor.checked_sub(token_sell_proportion)?;
+        let tokens = numerator.checked_add(denominator.checked_sub(1)?)?.checked_div(denominator)?;
+
+        Some(tokens)
+    }
+}
+
+impl BondingCurveModel for AMM {
+    fn buy_quote(&self, token_amount: u128) -> Option<u128> {
+        self.get_buy_price(token_amount.min(self.real_token_reserves))
+    }
+
+    fn sell_quote(&self, token_amount: u128) -> Option<u128> {
+        // apply_sell prices against the reserves after the tokens are added back
+        let mut amm = self.clone();
+        amm.apply_sell(token_amount).map(|result| result.sol_amount as u128)
+    }
+
+    fn spot_price(&self) -> Option<u128> {
+        self.virtual_sol_reserves.checked_mul(PRICE_SCALE)?.checked_div(self.virtual_token_reserves)
+    }
+
+    fn apply_buy(&mut self, token_amount: u128) -> Option<BuyResult> {
+        AMM::apply_buy(self, token_amount)
+    }
+
+    fn apply_sell(&mut self, token_amount: u128) -> Option<SellResult> {
+        AMM::apply_sell(self, token_amount)
+    }
+
+    fn reserves(&self) -> CurveReserves {
+        CurveReserves {
+            virtual_sol_reserves: self.virtual_sol_reserves,
+            virtual_token_reserves: self.virtual_token_reserves,
+            real_sol_reserves: self.real_sol_reserves,
+            real_token_reserves: self.real_token_reserves,
+        }
+    }
+
+    fn max_sell_amount(&self) -> u128 {
+        u64::MAX as u128
+    }
+
+    fn tokens_for_sol(&self, sol_amount: u128, fee_basis_points: u128) -> Option<u128> {
+        self.get_tokens_for_sol(sol_amount, fee_basis_points).map(|tokens| tokens.min(self.real_token_reserves))
+    }
+
+    fn tokens_for_sol_out(&self, sol_amount: u128, fee_basis_points: u128) -> Option<u128> {
+        self.get_tokens_for_sol_out(sol_amount, fee_basis_points)
+    }
 }
 
 
@@ -166,6 +281,47 @@ mod tests {
         assert_eq!(amm.real_sol_reserves, 410);    
     }
 
+    #[test]
+    fn test_get_tokens_for_sol_out() {
+        let amm = AMM::new(1000, 1000, 500, 500, 1000);
+
+        // Edge cases: nothing requested, more than the curve holds
+        assert_eq!(amm.get_tokens_for_sol_out(0, 0), None);
+        assert_eq!(amm.get_tokens_for_sol_out(501, 0), None);
+
+        // Normal case, one token less would not pay out 90
+        assert_eq!(amm.get_tokens_for_sol_out(90, 0), Some(99));
+        assert_eq!(AMM::new(1000, 1000, 500, 500, 1000).apply_sell(99).unwrap().sol_amount, 90);
+        assert_eq!(AMM::new(1000, 1000, 500, 500, 1000).apply_sell(98).unwrap().sol_amount, 89);
+
+        // 1% fee is added on top of the requested output
+        assert_eq!(amm.get_tokens_for_sol_out(450, 0), Some(819));
+        assert_eq!(amm.get_tokens_for_sol_out(450, 100), Some(832));
+    }
+
+    #[test]
+    fn test_get_tokens_for_sol_out_favors_curve() {
+        let virtual_sol_reserves = 30_000;
+        let virtual_token_reserves = 1_073_000;
+        let initial_virtual_token_reserves = 1_073_000;
+        let fee_basis_points = 50;
+
+        for sol_amount in 1..2000u128 {
+            let amm = AMM::new(virtual_sol_reserves, virtual_token_reserves, 3000, 1_000_000, initial_virtual_token_reserves);
+            let tokens = amm.get_tokens_for_sol_out(sol_amount, fee_basis_points).unwrap();
+
+            let mut amm = AMM::new(virtual_sol_reserves, virtual_token_reserves, 3000, 1_000_000, initial_virtual_token_reserves);
+            let result = amm.apply_sell(tokens).unwrap();
+            let fee = result.sol_amount as u128 * fee_basis_points / 10000;
+            assert!(result.sol_amount as u128 - fee >= sol_amount);
+
+            let mut amm = AMM::new(virtual_sol_reserves, virtual_token_reserves, 3000, 1_000_000, initial_virtual_token_reserves);
+            let result = amm.apply_sell(tokens - 1).unwrap();
+            let fee = result.sol_amount as u128 * fee_basis_points / 10000;
+            assert!(result.sol_amount as u128 - fee < sol_amount);
+        }
+    }
+
     #[test]
     fn test_get_sell_price() {
         let amm = AMM::new(1000, 1000, 500, 500, 1000);
@@ -220,4 +376,29 @@ mod tests {
         // Edge case: very large token amount
         assert_eq!(amm.get_buy_price(2000), None); 
     }
+
+    #[test]
+    fn test_get_tokens_for_sol() {
+        let amm = AMM::new(1000, 1000, 500, 500, 1000);
+
+        assert_eq!(amm.get_tokens_for_sol(0, 0), None);
+
+        // Inverse of get_buy_price without fee
+        assert_eq!(amm.get_tokens_for_sol(112, 0), Some(100));
+        assert_eq!(amm.get_tokens_for_sol(111, 0), Some(99));
+
+        // 1% fee is taken out of the budget first
+        assert_eq!(amm.get_tokens_for_sol(113, 100), Some(100));
+        assert_eq!(amm.get_tokens_for_sol(112, 100), Some(99));
+
+        // Price plus fee never exceeds the budget, one more token always would
+        for sol_amount in 1..5000u128 {
+            if let Some(tokens) = amm.get_tokens_for_sol(sol_amount, 100) {
+                let price = amm.get_buy_price(tokens).unwrap();
+                assert!(price + price / 100 <= sol_amount);
+                let next_price = amm.get_buy_price(tokens + 1).unwrap();
+                assert!(next_price + next_price / 100 > sol_amount);
+            }
+        }
+    }
 }
\ No newline at end of file
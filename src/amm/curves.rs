@@ -0,0 +1,534 @@
+use std::fmt;
+
+use crate::amm::fixed::{exp_neg_wad, exp_wad, ln_wad, softplus_wad, tanh_wad, wad_mul, WAD};
+use crate::amm::{BondingCurveModel, BuyResult, CurveReserves, SellResult, PRICE_SCALE};
+
+// A price as a function of tokens sold, with its exact integral.
+//
+// Both are in PRICE_SCALE units: price() is lamports per token base unit and
+// integral() is the lamports needed to go from zero to `supply` tokens sold.
+pub trait PriceFunction: fmt::Debug + Clone {
+    fn price(&self, supply: u128) -> Option<u128>;
+
+    fn integral(&self, supply: u128) -> Option<u128>;
+}
+
+// Prices trades as the area under a PriceFunction.
+//
+// A buy from s to s + t costs integral(s + t) - integral(s) rounded up and a
+// sell from s + t back to s pays the same difference rounded down. integral()
+// is deterministic, so the differences telescope over any sequence of trades
+// and real_sol_reserves never drops below integral(supply) / PRICE_SCALE.
+// In particular buying and then selling the same amount never returns more
+// SOL than was paid, whatever the rounding error of the integral itself.
+#[derive(Debug, Clone)]
+pub struct SupplyCurve<F: PriceFunction> {
+    pub price_function: F,
+    pub supply: u128,
+    pub real_sol_reserves: u128,
+    pub real_token_reserves: u128,
+}
+
+impl<F: PriceFunction> SupplyCurve<F> {
+    pub fn new(price_function: F, supply: u128, real_sol_reserves: u128, real_token_reserves: u128) -> Self {
+        SupplyCurve {
+            price_function,
+            supply,
+            real_sol_reserves,
+            real_token_reserves,
+        }
+    }
+
+    fn area(&self, from: u128, to: u128) -> Option<u128> {
+        let start = self.price_function.integral(from)?;
+        let end = self.price_function.integral(to)?;
+        end.checked_sub(start)
+    }
+}
+
+impl<F: PriceFunction> BondingCurveModel for SupplyCurve<F> {
+    fn buy_quote(&self, token_amount: u128) -> Option<u128> {
+        let token_amount = token_amount.min(self.real_token_reserves);
+        if token_amount == 0 {
+            return None;
+        }
+
+        let area = self.area(self.supply, self.supply.checked_add(token_amount)?)?;
+        let sol_amount = area.checked_add(PRICE_SCALE - 1)? / PRICE_SCALE;
+
+        // a flat stretch of the integral must still not hand out free tokens
+        Some(sol_amount.max(1))
+    }
+
+    fn sell_quote(&self, token_amount: u128) -> Option<u128> {
+        if token_amount == 0 || token_amount > self.supply {
+            return None;
+        }
+
+        let area = self.area(self.supply - token_amount, self.supply)?;
+        Some((area / PRICE_SCALE).min(self.real_sol_reserves))
+    }
+
+    fn spot_price(&self) -> Option<u128> {
+        self.price_function.price(self.supply)
+    }
+
+    fn apply_buy(&mut self, token_amount: u128) -> Option<BuyResult> {
+        let final_token_amount = token_amount.min(self.real_token_reserves);
+        let sol_amount = self.buy_quote(final_token_amount)?;
+
+        self.supply = self.supply.checked_add(final_token_amount)?;
+        self.real_token_reserves = self.real_token_reserves.checked_sub(final_token_amount)?;
+        self.real_sol_reserves = self.real_sol_reserves.checked_add(sol_amount)?;
+
+        Some(BuyResult {
+            token_amount: final_token_amount as u64,
+            sol_amount: sol_amount as u64,
+        })
+    }
+
+    fn apply_sell(&mut self, token_amount: u128) -> Option<SellResult> {
+        let sol_amount = self.sell_quote(token_amount)?;
+
+        self.supply = self.supply.checked_sub(token_amount)?;
+        self.real_token_reserves = self.real_token_reserves.checked_add(token_amount)?;
+        self.real_sol_reserves = self.real_sol_reserves.checked_sub(sol_amount)?;
+
+        Some(SellResult {
+            token_amount: token_amount as u64,
+            sol_amount: sol_amount as u64,
+        })
+    }
+
+    // there is no virtual liquidity, the virtual reserves are quoted at the
+    // spot price so virtual_sol_reserves / virtual_token_reserves still reads as a price.
+    // a sold out curve quotes PRICE_SCALE tokens instead so its price is readable until it migrates
+    fn reserves(&self) -> CurveReserves {
+        let spot_price = self.spot_price().unwrap_or(0);
+        let quoted_tokens = if self.real_token_reserves > 0 {
+            self.real_token_reserves
+        } else {
+            PRICE_SCALE
+        };
+        CurveReserves {
+            virtual_sol_reserves: spot_price.saturating_mul(quoted_tokens) / PRICE_SCALE,
+            virtual_token_reserves: quoted_tokens,
+            real_sol_reserves: self.real_sol_reserves,
+            real_token_reserves: self.real_token_reserves,
+        }
+    }
+
+    fn max_sell_amount(&self) -> u128 {
+        self.supply
+    }
+}
+
+// (s - offset) / width as a signed WAD
+fn scaled_offset(supply: u128, offset: u128, width: u128) -> Option<i128> {
+    let distance = (supply as i128).checked_sub(offset as i128)?;
+    let scaled = distance.unsigned_abs().checked_mul(WAD)? / width;
+    let scaled = i128::try_from(scaled).ok()?;
+    Some(if distance < 0 { -scaled } else { scaled })
+}
+
+// p(s) = base_price * e^(s / scale)
+#[derive(Debug, Clone)]
+pub struct Exponential {
+    pub base_price: u128,
+    pub scale: u128,
+}
+
+impl Exponential {
+    pub fn new(base_price: u128, scale: u128) -> Option<Self> {
+        if base_price == 0 || scale == 0 {
+            return None;
+        }
+        Some(Exponential { base_price, scale })
+    }
+}
+
+impl PriceFunction for Exponential {
+    fn price(&self, supply: u128) -> Option<u128> {
+        let growth = exp_wad(supply.checked_mul(WAD)? / self.scale)?;
+        wad_mul(self.base_price, growth)
+    }
+
+    // base_price * scale * (e^(s / scale) - 1)
+    fn integral(&self, supply: u128) -> Option<u128> {
+        let growth = exp_wad(supply.checked_mul(WAD)? / self.scale)?;
+        wad_mul(self.base_price.checked_mul(self.scale)?, growth - WAD)
+    }
+}
+
+// p(s) = base_price + slope * ln(1 + s / scale)
+#[derive(Debug, Clone)]
+pub struct Logarithmic {
+    pub base_price: u128,
+    pub slope: u128,
+    pub scale: u128,
+}
+
+impl Logarithmic {
+    pub fn new(base_price: u128, slope: u128, scale: u128) -> Option<Self> {
+        if base_price == 0 || scale == 0 {
+            return None;
+        }
+        Some(Logarithmic { base_price, slope, scale })
+    }
+}
+
+impl PriceFunction for Logarithmic {
+    fn price(&self, supply: u128) -> Option<u128> {
+        let log = ln_wad(WAD.checked_add(supply.checked_mul(WAD)? / self.scale)?)?;
+        self.base_price.checked_add(wad_mul(self.slope, log)?)
+    }
+
+    // base_price * s + slope * ((scale + s) * ln(1 + s / scale) - s)
+    fn integral(&self, supply: u128) -> Option<u128> {
+        let log = ln_wad(WAD.checked_add(supply.checked_mul(WAD)? / self.scale)?)?;
+        let log_area = wad_mul(self.slope.checked_mul(self.scale.checked_add(supply)?)?, log)?
+            .saturating_sub(self.slope.checked_mul(supply)?);
+        self.base_price.checked_mul(supply)?.checked_add(log_area)
+    }
+}
+
+// p(s) = base_price + height / (1 + e^(-(s - midpoint) / width))
+#[derive(Debug, Clone)]
+pub struct Sigmoid {
+    pub base_price: u128,
+    pub height: u128,
+    pub midpoint: u128,
+    pub width: u128,
+}
+
+impl Sigmoid {
+    pub fn new(base_price: u128, height: u128, midpoint: u128, width: u128) -> Option<Self> {
+        if base_price == 0 || width == 0 {
+            return None;
+        }
+        Some(Sigmoid { base_price, height, midpoint, width })
+    }
+}
+
+impl PriceFunction for Sigmoid {
+    fn price(&self, supply: u128) -> Option<u128> {
+        let y = scaled_offset(supply, self.midpoint, self.width)?;
+        let e = exp_neg_wad(y.unsigned_abs());
+        let logistic = if y >= 0 {
+            WAD * WAD / (WAD + e)
+        } else {
+            e * WAD / (WAD + e)
+        };
+        self.base_price.checked_add(wad_mul(self.height, logistic)?)
+    }
+
+    // base_price * s + height * width * (softplus(y(s)) - softplus(y(0)))
+    fn integral(&self, supply: u128) -> Option<u128> {
+        let start = softplus_wad(scaled_offset(0, self.midpoint, self.width)?)?;
+        let end = softplus_wad(scaled_offset(supply, self.midpoint, self.width)?)?;
+        let logistic_area = wad_mul(self.height.checked_mul(self.width)?, end.saturating_sub(start))?;
+        self.base_price.checked_mul(supply)?.checked_add(logistic_area)
+    }
+}
+
+// p(s) = base_price + peak * sech^2((s - center) / width)
+#[derive(Debug, Clone)]
+pub struct Bell {
+    pub base_price: u128,
+    pub peak: u128,
+    pub center: u128,
+    pub width: u128,
+}
+
+impl Bell {
+    pub fn new(base_price: u128, peak: u128, center: u128, width: u128) -> Option<Self> {
+        if base_price == 0 || width == 0 {
+            return None;
+        }
+        Some(Bell { base_price, peak, center, width })
+    }
+}
+
+impl PriceFunction for Bell {
+    fn price(&self, supply: u128) -> Option<u128> {
+        let tanh = tanh_wad(scaled_offset(supply, self.center, self.width)?).unsigned_abs();
+        let sech_squared = WAD - tanh * tanh / WAD;
+        self.base_price.checked_add(wad_mul(self.peak, sech_squared)?)
+    }
+
+    // base_price * s + peak * width * (tanh(y(s)) - tanh(y(0)))
+    fn integral(&self, supply: u128) -> Option<u128> {
+        let start = tanh_wad(scaled_offset(0, self.center, self.width)?);
+        let end = tanh_wad(scaled_offset(supply, self.center, self.width)?);
+        let bell_area = wad_mul(self.peak.checked_mul(self.width)?, end.saturating_sub(start).max(0) as u128)?;
+        self.base_price.checked_mul(supply)?.checked_add(bell_area)
+    }
+}
+
+// linear interpolation between (supply, price) breakpoints, flat after the last one
+#[derive(Debug, Clone)]
+pub struct PiecewiseLinear {
+    pub breakpoints: Vec<(u128, u128)>,
+}
+
+impl PiecewiseLinear {
+    // breakpoints must start at zero supply with a non zero price, supplies
+    // strictly increase and prices never decrease
+    pub fn new(breakpoints: Vec<(u128, u128)>) -> Option<Self> {
+        let (first_supply, first_price) = *breakpoints.first()?;
+        if first_supply != 0 || first_price == 0 {
+            return None;
+        }
+
+        let monotone = breakpoints
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0 && pair[0].1 <= pair[1].1);
+        if !monotone {
+            return None;
+        }
+
+        Some(PiecewiseLinear { breakpoints })
+    }
+
+    // price at supply inside the segment starting at breakpoint index
+    fn interpolate(&self, index: usize, supply: u128) -> Option<u128> {
+        let (start_supply, start_price) = self.breakpoints[index];
+        match self.breakpoints.get(index + 1) {
+            Some(&(end_supply, end_price)) => {
+                let rise = (end_price - start_price).checked_mul(supply - start_supply)?;
+                Some(start_price + rise / (end_supply - start_supply))
+            }
+            None => Some(start_price),
+        }
+    }
+
+    // area of the segment starting at breakpoint index, from its start up to supply
+    fn segment_area(&self, index: usize, supply: u128) -> Option<u128> {
+        let (start_supply, start_price) = self.breakpoints[index];
+        let price = self.interpolate(index, supply)?;
+        start_price.checked_add(price)?.checked_mul(supply - start_supply).map(|area| area / 2)
+    }
+
+    fn segment_index(&self, supply: u128) -> usize {
+        self.breakpoints
+            .iter()
+            .rposition(|&(start_supply, _)| start_supply <= supply)
+            .unwrap_or(0)
+    }
+}
+
+impl PriceFunction for PiecewiseLinear {
+    fn price(&self, supply: u128) -> Option<u128> {
+        self.interpolate(self.segment_index(supply), supply)
+    }
+
+    // whole trapezoids up to the segment holding supply, then the partial one
+    fn integral(&self, supply: u128) -> Option<u128> {
+        let index = self.segment_index(supply);
+        let mut area = 0u128;
+        for i in 0..index {
+            area = area.checked_add(self.segment_area(i, self.breakpoints[i + 1].0)?)?;
+        }
+        area.checked_add(self.segment_area(index, supply)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVE_TOKENS: u128 = 800_000_000_000_000;
+
+    fn exponential() -> Exponential {
+        Exponential::new(28_000_000, 200_000_000_000_000).unwrap()
+    }
+
+    fn logarithmic() -> Logarithmic {
+        Logarithmic::new(28_000_000, 40_000_000, 50_000_000_000_000).unwrap()
+    }
+
+    fn sigmoid() -> Sigmoid {
+        Sigmoid::new(28_000_000, 200_000_000, 400_000_000_000_000, 80_000_000_000_000).unwrap()
+    }
+
+    fn bell() -> Bell {
+        Bell::new(28_000_000, 200_000_000, 400_000_000_000_000, 100_000_000_000_000).unwrap()
+    }
+
+    fn piecewise_linear() -> PiecewiseLinear {
+        PiecewiseLinear::new(vec![
+            (0, 28_000_000),
+            (100_000_000_000_000, 40_000_000),
+            (300_000_000_000_000, 40_000_000),
+            (600_000_000_000_000, 150_000_000),
+            (700_000_000_000_000, 400_000_000),
+        ])
+        .unwrap()
+    }
+
+    // trapezoid rule on the float version of the price
+    fn numeric_area<F: PriceFunction>(price_function: &F, from: u128, to: u128) -> f64 {
+        let steps = 2000;
+        let step = (to - from) as f64 / steps as f64;
+        let mut area = 0.0;
+        for i in 0..steps {
+            let a = from as f64 + step * i as f64;
+            let b = a + step;
+            let price_a = price_function.price(a as u128).unwrap() as f64;
+            let price_b = price_function.price(b as u128).unwrap() as f64;
+            area += (price_a + price_b) / 2.0 * step;
+        }
+        area
+    }
+
+    fn assert_integral_matches_price<F: PriceFunction>(price_function: F) {
+        for i in 0..8u128 {
+            let from = i * CURVE_TOKENS / 8;
+            let to = from + CURVE_TOKENS / 8;
+            let exact = (price_function.integral(to).unwrap() - price_function.integral(from).unwrap()) as f64;
+            let numeric = numeric_area(&price_function, from, to);
+            assert!(((exact - numeric) / numeric).abs() < 1e-6, "{:?} {} {}", price_function, exact, numeric);
+        }
+    }
+
+    fn assert_round_trip<F: PriceFunction>(price_function: F) {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        let mut curve = SupplyCurve::new(price_function, 0, 0, CURVE_TOKENS);
+        for _ in 0..500 {
+            let token_amount = (next() as u128 % (CURVE_TOKENS / 20)) + 1;
+            let buy_result = match curve.apply_buy(token_amount) {
+                Some(result) => result,
+                None => break,
+            };
+
+            // buying then selling the same amount never returns more than was paid
+            let sell_back = curve.sell_quote(buy_result.token_amount as u128).unwrap();
+            assert!(sell_back <= buy_result.sol_amount as u128);
+
+            // randomly unwind part of the position
+            if next() % 3 == 0 {
+                let sell_amount = next() as u128 % curve.supply + 1;
+                curve.apply_sell(sell_amount).unwrap();
+            }
+
+            // the curve always holds at least the area under the price
+            let owed = curve.price_function.integral(curve.supply).unwrap() / PRICE_SCALE;
+            assert!(curve.real_sol_reserves >= owed);
+        }
+
+        // selling everything back is always covered
+        let supply = curve.supply;
+        if supply > 0 {
+            curve.apply_sell(supply).unwrap();
+        }
+        assert_eq!(curve.supply, 0);
+        assert_eq!(curve.real_token_reserves, CURVE_TOKENS);
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert!(Exponential::new(0, 1).is_none());
+        assert!(Exponential::new(1, 0).is_none());
+        assert!(Logarithmic::new(1, 1, 0).is_none());
+        assert!(Sigmoid::new(1, 1, 1, 0).is_none());
+        assert!(Bell::new(1, 1, 1, 0).is_none());
+
+        assert!(PiecewiseLinear::new(vec![]).is_none());
+        assert!(PiecewiseLinear::new(vec![(1, 1), (2, 2)]).is_none());
+        assert!(PiecewiseLinear::new(vec![(0, 0), (2, 2)]).is_none());
+        // supplies must strictly increase
+        assert!(PiecewiseLinear::new(vec![(0, 1), (2, 2), (2, 3)]).is_none());
+        // prices must not decrease
+        assert!(PiecewiseLinear::new(vec![(0, 2), (2, 1)]).is_none());
+        assert!(PiecewiseLinear::new(vec![(0, 1)]).is_some());
+    }
+
+    #[test]
+    fn test_exponential() {
+        let price_function = exponential();
+        assert_eq!(price_function.price(0), Some(28_000_000));
+        assert_eq!(price_function.integral(0), Some(0));
+        assert_integral_matches_price(exponential());
+        assert_round_trip(exponential());
+    }
+
+    #[test]
+    fn test_logarithmic() {
+        let price_function = logarithmic();
+        assert_eq!(price_function.price(0), Some(28_000_000));
+        assert_eq!(price_function.integral(0), Some(0));
+        assert_integral_matches_price(logarithmic());
+        assert_round_trip(logarithmic());
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        let price_function = sigmoid();
+        assert_eq!(price_function.price(400_000_000_000_000), Some(128_000_000));
+        assert_eq!(price_function.integral(0), Some(0));
+        assert_integral_matches_price(sigmoid());
+        assert_round_trip(sigmoid());
+    }
+
+    #[test]
+    fn test_bell() {
+        let price_function = bell();
+        assert_eq!(price_function.price(400_000_000_000_000), Some(228_000_000));
+        assert_eq!(price_function.integral(0), Some(0));
+        assert_integral_matches_price(bell());
+        assert_round_trip(bell());
+    }
+
+    #[test]
+    fn test_sold_out_reserves_keep_the_price() {
+        let mut curve = SupplyCurve::new(exponential(), 0, 0, CURVE_TOKENS);
+        curve.apply_buy(CURVE_TOKENS).unwrap();
+        assert_eq!(curve.real_token_reserves, 0);
+
+        let reserves = curve.reserves();
+        assert!(reserves.virtual_token_reserves > 0);
+        assert_eq!(
+            reserves.virtual_sol_reserves * PRICE_SCALE / reserves.virtual_token_reserves,
+            curve.spot_price().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_buy_quote_rounds_up_and_sell_quote_rounds_down() {
+        let curve = SupplyCurve::new(exponential(), CURVE_TOKENS / 2, 1_000_000_000_000, CURVE_TOKENS / 2);
+        let area = curve.area(CURVE_TOKENS / 2, CURVE_TOKENS / 2 + 12_345).unwrap();
+        assert_eq!(curve.buy_quote(12_345), Some(area.div_ceil(PRICE_SCALE)));
+
+        let area = curve.area(CURVE_TOKENS / 2 - 12_345, CURVE_TOKENS / 2).unwrap();
+        assert_eq!(curve.sell_quote(12_345), Some(area / PRICE_SCALE));
+
+        // nothing to sell beyond what was bought
+        assert_eq!(curve.sell_quote(CURVE_TOKENS / 2 + 1), None);
+    }
+
+    #[test]
+    fn test_piecewise_linear() {
+        let price_function = piecewise_linear();
+        assert_eq!(price_function.price(0), Some(28_000_000));
+        assert_eq!(price_function.price(50_000_000_000_000), Some(34_000_000));
+        assert_eq!(price_function.price(200_000_000_000_000), Some(40_000_000));
+        assert_eq!(price_function.price(700_000_000_000_000), Some(400_000_000));
+        // flat after the last breakpoint
+        assert_eq!(price_function.price(750_000_000_000_000), Some(400_000_000));
+
+        // trapezoids are exact for a linear price
+        assert_eq!(price_function.integral(0), Some(0));
+        assert_eq!(price_function.integral(100_000_000_000_000), Some(3_400_000_000_000_000_000_000));
+        assert_eq!(price_function.integral(300_000_000_000_000), Some(11_400_000_000_000_000_000_000));
+
+        assert_integral_matches_price(piecewise_linear());
+        assert_round_trip(piecewise_linear());
+    }
+}
@@ -0,0 +1,187 @@
+// 18 decimal fixed point helpers for the curve integrals, all rounding down
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+pub const LN_2_WAD: u128 = 693_147_180_559_945_309;
+
+// e^MAX_EXP_WAD still fits in a u128 after the 2^k shift
+pub const MAX_EXP_WAD: u128 = 40 * WAD;
+
+// x * y / WAD without overflowing when only the result fits
+pub fn wad_mul(x: u128, y: u128) -> Option<u128> {
+    let (x_high, x_low) = (x / WAD, x % WAD);
+    let (y_high, y_low) = (y / WAD, y % WAD);
+
+    x_high
+        .checked_mul(y_high)?
+        .checked_mul(WAD)?
+        .checked_add(x_high.checked_mul(y_low)?)?
+        .checked_add(x_low.checked_mul(y_high)?)?
+        .checked_add(x_low.checked_mul(y_low)? / WAD)
+}
+
+pub fn wad_div(x: u128, y: u128) -> Option<u128> {
+    x.checked_mul(WAD)?.checked_div(y)
+}
+
+// e^x for x >= 0, as 2^k * e^r with r in [0, ln 2)
+pub fn exp_wad(x: u128) -> Option<u128> {
+    if x > MAX_EXP_WAD {
+        return None;
+    }
+
+    let k = x / LN_2_WAD;
+    let r = x - k * LN_2_WAD;
+
+    // taylor series, every term rounds down so the sum never overshoots e^r
+    let mut term = WAD;
+    let mut sum = WAD;
+    let mut n = 1u128;
+    while term > 0 {
+        term = term * r / WAD / n;
+        sum += term;
+        n += 1;
+    }
+
+    sum.checked_mul(1u128 << k)
+}
+
+// e^x for x <= 0, returned in [0, WAD]
+pub fn exp_neg_wad(x: u128) -> u128 {
+    match exp_wad(x) {
+        Some(e) => WAD * WAD / e,
+        None => 0,
+    }
+}
+
+// ln(x) for x >= WAD, as k * ln 2 + ln(m) with m in [1, 2)
+pub fn ln_wad(x: u128) -> Option<u128> {
+    if x < WAD {
+        return None;
+    }
+
+    let mut k = 0u128;
+    let mut m = x;
+    while m >= 2 * WAD {
+        m /= 2;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) <= 1/3
+    let z = (m - WAD) * WAD / (m + WAD);
+    let z_squared = z * z / WAD;
+    let mut power = z;
+    let mut sum = 0u128;
+    let mut n = 1u128;
+    while power > 0 {
+        sum += power / n;
+        power = power * z_squared / WAD;
+        n += 2;
+    }
+
+    k.checked_mul(LN_2_WAD)?.checked_add(2 * sum)
+}
+
+// ln(1 + e^y) for signed y, always positive
+pub fn softplus_wad(y: i128) -> Option<u128> {
+    if y >= 0 {
+        let y = y as u128;
+        y.checked_add(ln_wad(WAD + exp_neg_wad(y))?)
+    } else {
+        ln_wad(WAD + exp_neg_wad(y.unsigned_abs()))
+    }
+}
+
+// tanh(y) for signed y, in (-WAD, WAD)
+pub fn tanh_wad(y: i128) -> i128 {
+    // e^-2|y| keeps everything in [0, WAD]
+    let e = exp_neg_wad(y.unsigned_abs().saturating_mul(2));
+    let tanh_abs = ((WAD - e) * WAD / (WAD + e)) as i128;
+
+    if y >= 0 {
+        tanh_abs
+    } else {
+        -tanh_abs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(x: u128) -> f64 {
+        x as f64 / WAD as f64
+    }
+
+    #[test]
+    fn test_wad_mul() {
+        assert_eq!(wad_mul(3 * WAD, WAD / 2), Some(3 * WAD / 2));
+        assert_eq!(wad_mul(7, WAD), Some(7));
+
+        // x * y overflows but the result does not
+        let big = 10u128.pow(30);
+        assert_eq!(wad_mul(big, 5 * WAD), Some(5 * big));
+        assert_eq!(wad_mul(u128::MAX, 2 * WAD), None);
+    }
+
+    #[test]
+    fn test_exp_wad() {
+        assert_eq!(exp_wad(0), Some(WAD));
+        assert_eq!(exp_wad(MAX_EXP_WAD + 1), None);
+
+        for i in 0..400u128 {
+            let x = i * WAD / 10;
+            let expected = (to_f64(x)).exp();
+            let actual = to_f64(exp_wad(x).unwrap());
+            assert!(((actual - expected) / expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_exp_wad_is_monotone() {
+        // around a multiple of ln 2 the 2^k shift takes over from the series
+        let boundary = 3 * LN_2_WAD;
+        let mut last = exp_wad(boundary - 1000).unwrap();
+        for x in boundary - 999..boundary + 1000 {
+            let e = exp_wad(x).unwrap();
+            assert!(e >= last);
+            last = e;
+        }
+    }
+
+    #[test]
+    fn test_ln_wad() {
+        assert_eq!(ln_wad(WAD), Some(0));
+        assert_eq!(ln_wad(WAD - 1), None);
+
+        for i in 1..400u128 {
+            let x = WAD + i * i * WAD / 7;
+            let expected = to_f64(x).ln();
+            let actual = to_f64(ln_wad(x).unwrap());
+            assert!((actual - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_ln_wad_is_monotone() {
+        let boundary = 4 * WAD;
+        let mut last = ln_wad(boundary - 1000).unwrap();
+        for x in boundary - 999..boundary + 1000 {
+            let l = ln_wad(x).unwrap();
+            assert!(l >= last);
+            last = l;
+        }
+    }
+
+    #[test]
+    fn test_softplus_and_tanh() {
+        for i in -200..200i128 {
+            let y = i * WAD as i128 / 20;
+            let y_f64 = y as f64 / WAD as f64;
+
+            let softplus = to_f64(softplus_wad(y).unwrap());
+            assert!((softplus - (1.0 + y_f64.exp()).ln()).abs() < 1e-12);
+
+            let tanh = tanh_wad(y) as f64 / WAD as f64;
+            assert!((tanh - y_f64.tanh()).abs() < 1e-12);
+        }
+    }
+}
@@ -1,2 +1,8 @@
 pub mod amm;
-pub use amm::*;
\ No newline at end of file
+pub mod curves;
+pub mod fixed;
+pub mod model;
+
+pub use amm::*;
+pub use curves::*;
+pub use model::*;
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use crate::amm::{BuyResult, SellResult};
+
+// spot prices are lamports per token base unit, scaled by this factor
+pub const PRICE_SCALE: u128 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurveReserves {
+    pub virtual_sol_reserves: u128,
+    pub virtual_token_reserves: u128,
+    pub real_sol_reserves: u128,
+    pub real_token_reserves: u128,
+}
+
+pub trait BondingCurveModel: fmt::Debug {
+    // SOL the curve charges for token_amount, before fees
+    fn buy_quote(&self, token_amount: u128) -> Option<u128>;
+
+    // SOL the curve pays for token_amount, before fees
+    fn sell_quote(&self, token_amount: u128) -> Option<u128>;
+
+    fn spot_price(&self) -> Option<u128>;
+
+    fn apply_buy(&mut self, token_amount: u128) -> Option<BuyResult>;
+
+    fn apply_sell(&mut self, token_amount: u128) -> Option<SellResult>;
+
+    fn reserves(&self) -> CurveReserves;
+
+    // upper bound on a single sell, tokens above this get no quote
+    fn max_sell_amount(&self) -> u128;
+
+    // the most tokens whose buy_quote plus fee fits in sol_amount
+    fn tokens_for_sol(&self, sol_amount: u128, fee_basis_points: u128) -> Option<u128> {
+        let fits = |tokens: u128| -> bool {
+            match self.buy_quote(tokens) {
+                Some(sol_cost) => match sol_cost.checked_mul(fee_basis_points) {
+                    Some(scaled_fee) => sol_cost.saturating_add(scaled_fee / 10000) <= sol_amount,
+                    None => false,
+                },
+                None => false,
+            }
+        };
+
+        let mut low = 0u128;
+        let mut high = self.reserves().real_token_reserves;
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        if low == 0 {
+            return None;
+        }
+
+        Some(low)
+    }
+
+    // the fewest tokens whose sell_quote minus fee is at least sol_amount
+    fn tokens_for_sol_out(&self, sol_amount: u128, fee_basis_points: u128) -> Option<u128> {
+        if sol_amount == 0 {
+            return None;
+        }
+
+        let covers = |tokens: u128| -> bool {
+            match self.sell_quote(tokens) {
+                Some(sol_out) => match sol_out.checked_mul(fee_basis_points) {
+                    Some(scaled_fee) => sol_out.saturating_sub(scaled_fee / 10000) >= sol_amount,
+                    None => false,
+                },
+                None => false,
+            }
+        };
+
+        let mut low = 1u128;
+        let mut high = self.max_sell_amount();
+        if !covers(high) {
+            return None;
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if covers(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        Some(low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amm::{BondingCurveModel, BuyResult, CurveReserves, SellResult, AMM};
+
+    // hides the closed form inverses of AMM so the default searches are exercised
+    #[derive(Debug)]
+    struct SearchOnly(AMM);
+
+    impl BondingCurveModel for SearchOnly {
+        fn buy_quote(&self, token_amount: u128) -> Option<u128> {
+            self.0.buy_quote(token_amount)
+        }
+
+        fn sell_quote(&self, token_amount: u128) -> Option<u128> {
+            self.0.sell_quote(token_amount)
+        }
+
+        fn spot_price(&self) -> Option<u128> {
+            self.0.spot_price()
+        }
+
+        fn apply_buy(&mut self, token_amount: u128) -> Option<BuyResult> {
+            BondingCurveModel::apply_buy(&mut self.0, token_amount)
+        }
+
+        fn apply_sell(&mut self, token_amount: u128) -> Option<SellResult> {
+            BondingCurveModel::apply_sell(&mut self.0, token_amount)
+        }
+
+        fn reserves(&self) -> CurveReserves {
+            self.0.reserves()
+        }
+
+        fn max_sell_amount(&self) -> u128 {
+            self.0.max_sell_amount()
+        }
+    }
+
+    #[test]
+    fn test_default_inverses_match_amm() {
+        let amm = AMM::new(30_000, 1_073_000, 2000, 800_000, 1_073_000);
+        let search = SearchOnly(amm.clone());
+
+        for sol_amount in 1..3000u128 {
+            assert_eq!(search.tokens_for_sol(sol_amount, 100), amm.tokens_for_sol(sol_amount, 100));
+            assert_eq!(search.tokens_for_sol_out(sol_amount, 100), amm.tokens_for_sol_out(sol_amount, 100));
+        }
+    }
+
+    #[test]
+    fn test_amm_spot_price() {
+        let amm = AMM::new(1000, 4000, 0, 4000, 4000);
+        assert_eq!(amm.spot_price(), Some(250_000_000_000));
+    }
+}
@@ -1,8 +1,8 @@
 use anchor_lang::{prelude::*, solana_program::system_instruction};
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
 use crate::{
-    amm, calculate_fee, state::{BondingCurve, Global}, CompleteEvent, CurveLaunchpadError, TradeEvent
+    allowlist_leaf, split_fee, verify_merkle_proof, state::{Allowlist, BondingCurve, BuyVesting, CreatorVault, CustomCurve, FeeVault, Global, WalletPurchase}, BuyVestedEvent, CompleteEvent, CurveLaunchpadError, TradeEvent
 };
 
 #[event_cpi]
@@ -37,6 +37,36 @@ pub struct Buy<'info> {
     )]
     bonding_curve_token_account: Box<Account<'info, TokenAccount>>,
 
+    #[account(
+        seeds = [CustomCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    custom_curve: Option<Account<'info, CustomCurve>>,
+
+    #[account(
+        mut,
+        seeds = [CreatorVault::SEED_PREFIX, bonding_curve.creator.as_ref()],
+        bump,
+    )]
+    creator_vault: Box<Account<'info, CreatorVault>>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX, referrer_fee_vault.owner.as_ref()],
+        bump,
+    )]
+    referrer_fee_vault: Option<Box<Account<'info, FeeVault>>>,
+
+    //required while the launch protection wallet cap or the presale is active
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + WalletPurchase::INIT_SPACE,
+        seeds = [WalletPurchase::SEED_PREFIX, mint.to_account_info().key.as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    wallet_purchase: Option<Box<Account<'info, WalletPurchase>>>,
+
     #[account(
         mut,
         associated_token::mint = mint,
@@ -44,17 +74,92 @@ pub struct Buy<'info> {
     )]
     user_token_account: Box<Account<'info, TokenAccount>>,
 
+    //only needed for buy_and_vest
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + BuyVesting::INIT_SPACE,
+        seeds = [BuyVesting::SEED_PREFIX, mint.to_account_info().key.as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    buy_vesting: Option<Box<Account<'info, BuyVesting>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [BuyVesting::TOKEN_SEED_PREFIX, mint.to_account_info().key.as_ref(), user.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = buy_vesting,
+    )]
+    buy_vesting_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
     system_program: Program<'info, System>,
 
     token_program: Program<'info, Token>,
 }
 
 pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()> {
+    buy_tokens(ctx, token_amount, max_sol_cost, None, false, None)
+}
+
+//the bought tokens go to the user's vesting account and release on the launch's schedule
+pub fn buy_and_vest(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()> {
+    require!(
+        ctx.accounts.bonding_curve.vesting_schedule.is_some(),
+        CurveLaunchpadError::VestingDisabled,
+    );
+
+    buy_tokens(ctx, token_amount, max_sol_cost, None, true, None)
+}
+
+//proves (user, max_allocation) against the presale root, the allocation is only enforced during the presale
+pub fn buy_allowlisted(
+    ctx: Context<Buy>,
+    token_amount: u64,
+    max_sol_cost: u64,
+    max_allocation: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    verify_allowlist_proof(&ctx.accounts.bonding_curve.allowlist, &ctx.accounts.user.key(), max_allocation, &proof)?;
+
+    buy_tokens(ctx, token_amount, max_sol_cost, Some(max_allocation), false, None)
+}
+
+fn verify_allowlist_proof(allowlist: &Allowlist, user: &Pubkey, max_allocation: u64, proof: &[[u8; 32]]) -> Result<()> {
+    require!(
+        allowlist.is_enabled(),
+        CurveLaunchpadError::InvalidAllowlist,
+    );
+
+    let leaf = allowlist_leaf(&user.to_bytes(), max_allocation);
+    require!(
+        verify_merkle_proof(proof, &allowlist.merkle_root, leaf),
+        CurveLaunchpadError::InvalidAllowlistProof,
+    );
+
+    Ok(())
+}
+
+fn buy_tokens(
+    ctx: Context<Buy>,
+    token_amount: u64,
+    max_sol_cost: u64,
+    allowlist_allocation: Option<u64>,
+    vest: bool,
+    protocol_fee_basis_points: Option<u64>,
+) -> Result<()> {
     require!(
         ctx.accounts.global.initialized,
         CurveLaunchpadError::NotInitialized
     );
 
+    //bonding curve is not complete
+    require!(
+        !ctx.accounts.bonding_curve.complete && !ctx.accounts.bonding_curve.migrated,
+        CurveLaunchpadError::BondingCurveComplete,
+    );
+
     //invalid fee recipient
     require!(
         ctx.accounts.fee_recipient.key == &ctx.accounts.global.fee_recipient,
@@ -69,23 +174,99 @@ pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()
 
     require!(token_amount > 0, CurveLaunchpadError::MinBuy,);
 
-    let targe_token_amount = if ctx.accounts.bonding_curve_token_account.amount < token_amount {
+    let clock = Clock::get()?;
+    let launch_protection = ctx.accounts.bonding_curve.launch_protection;
+    let allowlist = ctx.accounts.bonding_curve.allowlist;
+
+    //during the presale only allowlisted wallets can buy, otherwise trading is closed until public trading opens
+    let presale_allocation = if allowlist.is_presale(clock.unix_timestamp) {
+        Some(allowlist_allocation.ok_or(CurveLaunchpadError::TradingNotStarted)?)
+    } else {
+        require!(
+            ctx.accounts.bonding_curve.is_public_trading_open(clock.unix_timestamp, clock.slot),
+            CurveLaunchpadError::TradingNotStarted,
+        );
+        None
+    };
+
+    require!(
+        launch_protection.max_tokens_per_transaction == 0
+            || token_amount <= launch_protection.max_tokens_per_transaction,
+        CurveLaunchpadError::MaxTokensPerTransactionExceeded,
+    );
+
+    let target_token_amount = if ctx.accounts.bonding_curve_token_account.amount < token_amount {
         ctx.accounts.bonding_curve_token_account.amount
     } else {
         token_amount
     };
 
-    let mut amm = amm::amm::AMM::new(
-        ctx.accounts.bonding_curve.virtual_sol_reserves as u128,
-        ctx.accounts.bonding_curve.virtual_token_reserves as u128,
-        ctx.accounts.bonding_curve.real_sol_reserves as u128,
-        ctx.accounts.bonding_curve.real_token_reserves as u128,
-        ctx.accounts.global.initial_virtual_token_reserves as u128,
-    );
+    let mut curve_model = ctx
+        .accounts
+        .bonding_curve
+        .model(ctx.accounts.custom_curve.as_deref())?;
+
+    //a referrer can't be the buyer
+    if let Some(referrer_fee_vault) = &ctx.accounts.referrer_fee_vault {
+        require!(
+            referrer_fee_vault.owner != ctx.accounts.user.key(),
+            CurveLaunchpadError::InvalidReferrer,
+        );
+    }
+
+    let buy_result = curve_model
+        .apply_buy(target_token_amount as u128)
+        .ok_or(CurveLaunchpadError::InsufficientTokens)?;
+
+    if let Some(max_allocation) = presale_allocation {
+        let wallet_purchase = ctx
+            .accounts
+            .wallet_purchase
+            .as_ref()
+            .ok_or(CurveLaunchpadError::WalletPurchaseRequired)?;
+        let tokens_bought = wallet_purchase
+            .tokens_bought
+            .checked_add(buy_result.token_amount)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+        require!(
+            tokens_bought <= max_allocation,
+            CurveLaunchpadError::AllowlistAllocationExceeded,
+        );
+    }
 
-    let buy_result = amm.apply_buy(targe_token_amount as u128).unwrap();
-    let fee = calculate_fee(buy_result.sol_amount, ctx.accounts.global.fee_basis_points);
-    let buy_amount_with_fee = buy_result.sol_amount + fee;
+    //each wallet is capped during the protected window
+    if launch_protection.is_wallet_capped(clock.unix_timestamp) {
+        let wallet_purchase = ctx
+            .accounts
+            .wallet_purchase
+            .as_ref()
+            .ok_or(CurveLaunchpadError::WalletPurchaseRequired)?;
+        let tokens_bought = wallet_purchase
+            .tokens_bought
+            .checked_add(buy_result.token_amount)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+        require!(
+            tokens_bought <= launch_protection.max_wallet_buy,
+            CurveLaunchpadError::MaxWalletBuyExceeded,
+        );
+    }
+
+    //the fee tier comes from the executed SOL amount, unless the caller already quoted with one
+    let global = &ctx.accounts.global;
+    let protocol_fee_basis_points = protocol_fee_basis_points.unwrap_or_else(|| {
+        global.protocol_fee_basis_points(
+            ctx.accounts.bonding_curve.progress_basis_points(),
+            buy_result.sol_amount,
+        )
+    });
+    let fees = split_fee(
+        buy_result.sol_amount,
+        protocol_fee_basis_points,
+        global.creator_fee_basis_points,
+        global.referrer_fee_basis_points,
+        ctx.accounts.referrer_fee_vault.is_some(),
+    );
+    let buy_amount_with_fee = buy_result.sol_amount + fees.total();
 
     //check if the amount of SOL to transfe plus fee is less than the max_sol_cost
     require!(
@@ -125,7 +306,7 @@ pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()
     let transfer_instruction = system_instruction::transfer(
         from_account.key,
         to_fee_recipient_account.key,
-        fee,
+        fees.protocol_fee,
     );
 
     anchor_lang::solana_program::program::invoke_signed(
@@ -138,6 +319,52 @@ pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()
         &[],
     )?;
 
+    //the creator share accrues in the creator vault, the referrer share in the referrer fee vault
+    let transfer_instruction = system_instruction::transfer(
+        from_account.key,
+        ctx.accounts.creator_vault.to_account_info().key,
+        fees.creator_fee,
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &transfer_instruction,
+        &[
+            from_account.to_account_info(),
+            ctx.accounts.creator_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[],
+    )?;
+
+    let creator_vault = &mut ctx.accounts.creator_vault;
+    creator_vault.total_accrued = creator_vault
+        .total_accrued
+        .checked_add(fees.creator_fee)
+        .ok_or(CurveLaunchpadError::CalculationError)?;
+
+    if let Some(referrer_fee_vault) = &mut ctx.accounts.referrer_fee_vault {
+        let transfer_instruction = system_instruction::transfer(
+            from_account.key,
+            referrer_fee_vault.to_account_info().key,
+            fees.referrer_fee,
+        );
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &transfer_instruction,
+            &[
+                from_account.to_account_info(),
+                referrer_fee_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[],
+        )?;
+
+        referrer_fee_vault.total_accrued = referrer_fee_vault
+            .total_accrued
+            .checked_add(fees.referrer_fee)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+    }
+
     let signer: [&[&[u8]]; 1] = [&[
         BondingCurve::SEED_PREFIX,
         ctx.accounts.mint.to_account_info().key.as_ref(),
@@ -145,25 +372,67 @@ pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()
     ]];
 
 
-    token::mint_to(
+    //vested buys are sent to the vesting account instead of the user
+    let destination = if vest {
+        ctx.accounts
+            .buy_vesting_token_account
+            .as_ref()
+            .ok_or(CurveLaunchpadError::VestingAccountsRequired)?
+            .to_account_info()
+    } else {
+        ctx.accounts.user_token_account.to_account_info()
+    };
+
+    //create mints the whole supply to the bonding curve, buys are paid out of it
+    token::transfer(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.mint.to_account_info().clone(),
-                to: ctx.accounts.user_token_account.to_account_info().clone(),
-                authority: ctx.accounts.bonding_curve.to_account_info().clone(),
+            Transfer {
+                from: ctx.accounts.bonding_curve_token_account.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.bonding_curve.to_account_info(),
             },
             &signer,
         ),
         buy_result.token_amount,
     )?;
 
+    if let Some(wallet_purchase) = &mut ctx.accounts.wallet_purchase {
+        wallet_purchase.bonding_curve = ctx.accounts.bonding_curve.key();
+        wallet_purchase.user = ctx.accounts.user.key();
+        wallet_purchase.tokens_bought = wallet_purchase
+            .tokens_bought
+            .checked_add(buy_result.token_amount)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+    }
+
+    if vest {
+        let schedule = ctx.accounts.bonding_curve.vesting_schedule;
+        let buy_vesting = ctx
+            .accounts
+            .buy_vesting
+            .as_mut()
+            .ok_or(CurveLaunchpadError::VestingAccountsRequired)?;
+        if buy_vesting.total_amount == 0 {
+            buy_vesting.bonding_curve = ctx.accounts.bonding_curve.key();
+            buy_vesting.user = ctx.accounts.user.key();
+            buy_vesting.schedule = schedule.ok_or(CurveLaunchpadError::VestingDisabled)?;
+        }
+        buy_vesting.add(buy_result.token_amount, clock.unix_timestamp)?;
+
+        emit_cpi!(BuyVestedEvent {
+            user: buy_vesting.user,
+            mint: *ctx.accounts.mint.to_account_info().key,
+            buy_vesting: buy_vesting.key(),
+            amount: buy_result.token_amount,
+            total_amount: buy_vesting.total_amount,
+            start_timestamp: buy_vesting.start_timestamp,
+        });
+    }
+
     //apply the buy to the bonding curve
     let bonding_curve = &mut ctx.accounts.bonding_curve;
-    bonding_curve.real_token_reserves = amm.real_token_reserves as u64;
-    bonding_curve.real_sol_reserves = amm.real_sol_reserves as u64;
-    bonding_curve.virtual_token_reserves = amm.virtual_token_reserves as u64;
-    bonding_curve.virtual_sol_reserves = amm.virtual_sol_reserves as u64;
+    bonding_curve.set_reserves(curve_model.reserves())?;
 
     emit_cpi!(TradeEvent {
         mint: *ctx.accounts.mint.to_account_info().key,
@@ -176,6 +445,16 @@ pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()
         virtual_token_reserves: bonding_curve.virtual_token_reserves,
         real_sol_reserves: bonding_curve.real_sol_reserves,
         real_token_reserves: bonding_curve.real_token_reserves,
+        protocol_fee: fees.protocol_fee,
+        creator_fee: fees.creator_fee,
+        referrer_fee: fees.referrer_fee,
+        referrer: ctx
+            .accounts
+            .referrer_fee_vault
+            .as_ref()
+            .map_or(Pubkey::default(), |referrer_fee_vault| referrer_fee_vault.owner),
+        protocol_fee_basis_points,
+        fee_basis_points: ctx.accounts.global.total_fee_basis_points(protocol_fee_basis_points),
     });
 
     if bonding_curve.real_token_reserves == 0 {
@@ -189,8 +468,79 @@ pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()
         });
     }
 
-    msg!("bonding_curve: {:?}", amm);
-
     Ok(())
 }
 
+pub fn buy_exact_sol_in(ctx: Context<Buy>, sol_amount: u64, min_tokens_out: u64) -> Result<()> {
+    buy_for_sol(ctx, sol_amount, min_tokens_out, None)
+}
+
+//exact-in counterpart of buy_allowlisted
+pub fn buy_exact_sol_in_allowlisted(
+    ctx: Context<Buy>,
+    sol_amount: u64,
+    min_tokens_out: u64,
+    max_allocation: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    verify_allowlist_proof(&ctx.accounts.bonding_curve.allowlist, &ctx.accounts.user.key(), max_allocation, &proof)?;
+
+    buy_for_sol(ctx, sol_amount, min_tokens_out, Some(max_allocation))
+}
+
+fn buy_for_sol(
+    ctx: Context<Buy>,
+    sol_amount: u64,
+    min_tokens_out: u64,
+    allowlist_allocation: Option<u64>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    let curve_model = ctx
+        .accounts
+        .bonding_curve
+        .model(ctx.accounts.custom_curve.as_deref())?;
+
+    //the fee tier is picked from the price of the tokens bought, not from the SOL spent on them
+    //and their fee, so size the buy with the tier of sol_amount and settle on the tier it executes at
+    let global = &ctx.accounts.global;
+    let progress_basis_points = ctx.accounts.bonding_curve.progress_basis_points();
+    let tokens_for_sol = |protocol_fee_basis_points: u64| {
+        curve_model
+            .tokens_for_sol(sol_amount as u128, global.total_fee_basis_points(protocol_fee_basis_points) as u128)
+            .ok_or(CurveLaunchpadError::MinBuy)
+    };
+
+    let estimated_sol_amount = curve_model
+        .buy_quote(tokens_for_sol(global.protocol_fee_basis_points(progress_basis_points, sol_amount))?)
+        .ok_or(CurveLaunchpadError::MinBuy)?;
+    let protocol_fee_basis_points = global.protocol_fee_basis_points(progress_basis_points, estimated_sol_amount as u64);
+    let tokens_for_sol = tokens_for_sol(protocol_fee_basis_points)?;
+
+    //cap at what is left on the curve, the unused SOL is never taken from the user
+    let token_amount = u64::try_from(tokens_for_sol)
+        .map_err(|_| CurveLaunchpadError::CalculationError)?
+        .min(ctx.accounts.bonding_curve.real_token_reserves)
+        .min(ctx.accounts.bonding_curve_token_account.amount);
+
+    require!(
+        token_amount >= min_tokens_out,
+        CurveLaunchpadError::MinTokenOutputExceeded,
+    );
+
+    //a capped buy is quoted at the tier of the price of what it executes, not of what it was sized for
+    let protocol_fee_basis_points = if (token_amount as u128) < tokens_for_sol {
+        let capped_sol_amount = curve_model
+            .buy_quote(token_amount as u128)
+            .ok_or(CurveLaunchpadError::InsufficientTokens)?;
+        global.protocol_fee_basis_points(progress_basis_points, capped_sol_amount as u64)
+    } else {
+        protocol_fee_basis_points
+    };
+
+    //sol_amount is the max cost, buy charges only the price of token_amount plus fee at the quoted tier
+    buy_tokens(ctx, token_amount, sol_amount, allowlist_allocation, false, Some(protocol_fee_basis_points))
+}
@@ -0,0 +1,78 @@
+use crate::{state::BuyVesting, BuyVestingClaimedEvent, CurveLaunchpadError};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimBuyVesting<'info> {
+    user: Signer<'info>,
+
+    mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [BuyVesting::SEED_PREFIX, mint.to_account_info().key.as_ref(), user.key().as_ref()],
+        bump,
+        has_one = user,
+    )]
+    buy_vesting: Box<Account<'info, BuyVesting>>,
+
+    #[account(
+        mut,
+        seeds = [BuyVesting::TOKEN_SEED_PREFIX, mint.to_account_info().key.as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    buy_vesting_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    user_token_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+//releases whatever has vested since the last claim
+pub fn claim_buy_vesting(ctx: Context<ClaimBuyVesting>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let amount = ctx.accounts.buy_vesting.releasable_amount(timestamp);
+    require!(amount > 0, CurveLaunchpadError::NothingToClaim);
+
+    let mint_key = ctx.accounts.mint.key();
+    let user_key = ctx.accounts.user.key();
+    let signer: [&[&[u8]]; 1] = [&[
+        BuyVesting::SEED_PREFIX,
+        mint_key.as_ref(),
+        user_key.as_ref(),
+        &[ctx.bumps.buy_vesting],
+    ]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buy_vesting_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.buy_vesting.to_account_info(),
+            },
+            &signer,
+        ),
+        amount,
+    )?;
+
+    let buy_vesting = &mut ctx.accounts.buy_vesting;
+    buy_vesting.released_amount += amount;
+
+    emit_cpi!(BuyVestingClaimedEvent {
+        user: user_key,
+        mint: mint_key,
+        buy_vesting: ctx.accounts.buy_vesting.key(),
+        amount,
+        released_amount: ctx.accounts.buy_vesting.released_amount,
+        timestamp,
+    });
+
+    Ok(())
+}
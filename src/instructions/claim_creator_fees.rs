@@ -0,0 +1,42 @@
+use crate::{state::CreatorVault, CreatorFeesClaimedEvent, CurveLaunchpadError};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CreatorVault::SEED_PREFIX, creator.key.as_ref()],
+        bump,
+        has_one = creator,
+    )]
+    creator_vault: Box<Account<'info, CreatorVault>>,
+
+    system_program: Program<'info, System>,
+}
+
+//sweeps the creator fees of every curve the creator launched in one go
+pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+    let creator_vault = &mut ctx.accounts.creator_vault;
+
+    let min_balance = Rent::get()?.minimum_balance(8 + CreatorVault::INIT_SPACE);
+    let amount = creator_vault.get_lamports().saturating_sub(min_balance);
+    require!(amount > 0, CurveLaunchpadError::NothingToClaim);
+
+    **creator_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.creator.try_borrow_mut_lamports()? += amount;
+
+    creator_vault.total_claimed += amount;
+
+    emit_cpi!(CreatorFeesClaimedEvent {
+        creator: *ctx.accounts.creator.to_account_info().key,
+        creator_vault: ctx.accounts.creator_vault.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
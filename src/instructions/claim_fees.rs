@@ -0,0 +1,42 @@
+use crate::{state::FeeVault, CurveLaunchpadError, FeesClaimedEvent};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimFees<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX, owner.key.as_ref()],
+        bump,
+        has_one = owner,
+    )]
+    fee_vault: Box<Account<'info, FeeVault>>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
+    let fee_vault = &mut ctx.accounts.fee_vault;
+
+    //everything above rent has been accrued from trades
+    let min_balance = Rent::get()?.minimum_balance(8 + FeeVault::INIT_SPACE);
+    let amount = fee_vault.get_lamports().saturating_sub(min_balance);
+    require!(amount > 0, CurveLaunchpadError::NothingToClaim);
+
+    **fee_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.owner.try_borrow_mut_lamports()? += amount;
+
+    fee_vault.total_claimed += amount;
+
+    emit_cpi!(FeesClaimedEvent {
+        owner: *ctx.accounts.owner.to_account_info().key,
+        fee_vault: ctx.accounts.fee_vault.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
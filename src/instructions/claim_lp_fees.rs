@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    calculate_fee, dynamic_amm,
+    state::{BondingCurve, Global, MigrationTarget},
+    CurveLaunchpadError, LpFeesClaimedEvent,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimLpFees<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(address = native_mint::ID)]
+    wsol_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [BondingCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    //the claimed fees land in the bonding curve's accounts before they are split
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bonding_curve,
+    )]
+    bonding_curve_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = bonding_curve,
+    )]
+    bonding_curve_wsol_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Using global state to validate fee_recipient account
+    #[account(address = global.fee_recipient)]
+    fee_recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = fee_recipient,
+    )]
+    fee_recipient_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    fee_recipient_wsol_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Using bonding curve state to validate creator account
+    #[account(address = bonding_curve.creator)]
+    creator: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+    )]
+    creator_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = creator,
+    )]
+    creator_wsol_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pool the curve migrated into
+    #[account(
+        mut,
+        address = bonding_curve.pool,
+    )]
+    pool: UncheckedAccount<'info>,
+
+    /// CHECK: LP token mint of the pool
+    #[account(mut)]
+    lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Lock escrow of the bonding curve for the migrated pool
+    #[account(
+        mut,
+        seeds = [b"lock_escrow", pool.key.as_ref(), bonding_curve.to_account_info().key.as_ref()],
+        bump,
+        seeds::program = dynamic_amm_program.key(),
+    )]
+    lock_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: LP account of the lock escrow
+    #[account(
+        mut,
+        address = get_associated_token_address(lock_escrow.key, lp_mint.key),
+    )]
+    escrow_vault: UncheckedAccount<'info>,
+
+    /// CHECK: LP account of the bonding curve, emptied into the escrow at migration
+    #[account(
+        mut,
+        address = get_associated_token_address(bonding_curve.to_account_info().key, lp_mint.key),
+    )]
+    bonding_curve_lp_account: UncheckedAccount<'info>,
+
+    /// CHECK: Token vault account of vault A
+    #[account(mut)]
+    a_token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Token vault account of vault B
+    #[account(mut)]
+    b_token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Vault account for the launched token
+    #[account(mut)]
+    a_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Vault account for WSOL
+    #[account(mut)]
+    b_vault: UncheckedAccount<'info>,
+
+    /// CHECK: LP token account of vault A
+    #[account(mut)]
+    a_vault_lp: UncheckedAccount<'info>,
+
+    /// CHECK: LP token account of vault B
+    #[account(mut)]
+    b_vault_lp: UncheckedAccount<'info>,
+
+    /// CHECK: LP token mint of vault A
+    #[account(mut)]
+    a_vault_lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: LP token mint of vault B
+    #[account(mut)]
+    b_vault_lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Vault program, the pool withdraws the fees through it
+    vault_program: UncheckedAccount<'info>,
+
+    dynamic_amm_program: Program<'info, dynamic_amm::program::DynamicAmm>,
+
+    token_program: Program<'info, Token>,
+
+    associated_token_program: Program<'info, AssociatedToken>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
+    require!(
+        ctx.accounts.bonding_curve.migrated,
+        CurveLaunchpadError::NotMigrated,
+    );
+
+    //only dynamic_amm pools have a lock escrow to claim from
+    require!(
+        ctx.accounts.bonding_curve.migration_target == MigrationTarget::DynamicAmm,
+        CurveLaunchpadError::InvalidMigrationTarget,
+    );
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer: [&[&[u8]]; 1] = [&[
+        BondingCurve::SEED_PREFIX,
+        mint_key.as_ref(),
+        &[ctx.bumps.bonding_curve],
+    ]];
+
+    let token_balance_before = ctx.accounts.bonding_curve_token_account.amount;
+    let wsol_balance_before = ctx.accounts.bonding_curve_wsol_account.amount;
+
+    let accounts = dynamic_amm::cpi::accounts::ClaimFee {
+        pool: ctx.accounts.pool.to_account_info(),
+        lp_mint: ctx.accounts.lp_mint.to_account_info(),
+        lock_escrow: ctx.accounts.lock_escrow.to_account_info(),
+        owner: ctx.accounts.bonding_curve.to_account_info(),
+        source_tokens: ctx.accounts.bonding_curve_lp_account.to_account_info(),
+        escrow_vault: ctx.accounts.escrow_vault.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        a_token_vault: ctx.accounts.a_token_vault.to_account_info(),
+        b_token_vault: ctx.accounts.b_token_vault.to_account_info(),
+        a_vault: ctx.accounts.a_vault.to_account_info(),
+        b_vault: ctx.accounts.b_vault.to_account_info(),
+        a_vault_lp: ctx.accounts.a_vault_lp.to_account_info(),
+        b_vault_lp: ctx.accounts.b_vault_lp.to_account_info(),
+        a_vault_lp_mint: ctx.accounts.a_vault_lp_mint.to_account_info(),
+        b_vault_lp_mint: ctx.accounts.b_vault_lp_mint.to_account_info(),
+        user_a_token: ctx.accounts.bonding_curve_token_account.to_account_info(),
+        user_b_token: ctx.accounts.bonding_curve_wsol_account.to_account_info(),
+        vault_program: ctx.accounts.vault_program.to_account_info(),
+    };
+    dynamic_amm::cpi::claim_fee(
+        CpiContext::new_with_signer(
+            ctx.accounts.dynamic_amm_program.to_account_info(),
+            accounts,
+            &signer,
+        ),
+        u64::MAX,
+    )?;
+
+    ctx.accounts.bonding_curve_token_account.reload()?;
+    ctx.accounts.bonding_curve_wsol_account.reload()?;
+    let claimed_token_amount = ctx.accounts.bonding_curve_token_account.amount - token_balance_before;
+    let claimed_wsol_amount = ctx.accounts.bonding_curve_wsol_account.amount - wsol_balance_before;
+
+    //the creator only shares the LP fees when the launch opted in
+    let creator_lp_fee_basis_points = if ctx.accounts.bonding_curve.creator_lp_fees {
+        ctx.accounts.global.creator_lp_fee_basis_points
+    } else {
+        0
+    };
+    let creator_token_fee = calculate_fee(claimed_token_amount, creator_lp_fee_basis_points);
+    let creator_wsol_fee = calculate_fee(claimed_wsol_amount, creator_lp_fee_basis_points);
+    let protocol_token_fee = claimed_token_amount - creator_token_fee;
+    let protocol_wsol_fee = claimed_wsol_amount - creator_wsol_fee;
+
+    let transfers = [
+        (&ctx.accounts.bonding_curve_token_account, &ctx.accounts.creator_token_account, creator_token_fee),
+        (&ctx.accounts.bonding_curve_wsol_account, &ctx.accounts.creator_wsol_account, creator_wsol_fee),
+        (&ctx.accounts.bonding_curve_token_account, &ctx.accounts.fee_recipient_token_account, protocol_token_fee),
+        (&ctx.accounts.bonding_curve_wsol_account, &ctx.accounts.fee_recipient_wsol_account, protocol_wsol_fee),
+    ];
+    for (from, to, amount) in transfers {
+        if amount == 0 {
+            continue;
+        }
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: ctx.accounts.bonding_curve.to_account_info(),
+                },
+                &signer,
+            ),
+            amount,
+        )?;
+    }
+
+    emit_cpi!(LpFeesClaimedEvent {
+        mint: mint_key,
+        bonding_curve: *ctx.accounts.bonding_curve.to_account_info().key,
+        pool: ctx.accounts.pool.key(),
+        protocol_token_fee,
+        protocol_wsol_fee,
+        creator_token_fee,
+        creator_wsol_fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,3 +1,12 @@
 pub const DEFAULT_DECIMALS: u32 = 6;
 pub const DEFAULT_TOKEN_LAMPORTS: u64 = (10 as u64).pow(DEFAULT_DECIMALS);
-pub const DEFAULT_TOKEN_SUPPLY: u64 = 1_000_000_000 * DEFAULT_TOKEN_LAMPORTS;
\ No newline at end of file
+pub const DEFAULT_TOKEN_SUPPLY: u64 = 1_000_000_000 * DEFAULT_TOKEN_LAMPORTS;
+pub const MAX_OPERATOR_FEE_BASIS_POINTS: u64 = 1000;
+pub const MAX_MIGRATION_FEE: u64 = 10_000_000_000;
+pub const MAX_PRESALE_DURATION: i64 = 7 * 24 * 60 * 60;
+//the lock program's lock_tokens bounds, an initial buy is checked against them before it is locked
+pub const MIN_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;
+pub const MAX_LOCK_DURATION: i64 = 2 * 365 * 24 * 60 * 60;
+pub const MIN_LOCK_AMOUNT: u64 = 2;
+//lent to the pool authority for the rent of a new dynamic_amm pool, the unused part is refunded
+pub const POOL_CREATION_LAMPORTS: u64 = 100_000_000;
\ No newline at end of file
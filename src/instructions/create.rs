@@ -1,18 +1,43 @@
 use crate::{
-    state::{BondingCurve, Global}, CreateEvent, CurveLaunchpadError, DEFAULT_DECIMALS
+    calculate_fee, split_fee, state::{BondingCurve, CreatorVault, CurveKind, CustomCurve, Global, LaunchParams, LaunchProtection, MigrationTarget, VestingSchedule, CURVE_PARAMS_LEN}, token_lock, CompleteEvent, CreateEvent, CurveLaunchpadError, TradeEvent, DEFAULT_DECIMALS, MAX_LOCK_DURATION, MIN_LOCK_AMOUNT, MIN_LOCK_DURATION
+};
+use anchor_lang::{
+    prelude::*,
+    system_program,
 };
-use anchor_lang::{prelude::*, solana_program::program::invoke};
 use anchor_spl::{
-    associated_token::{spl_associated_token_account::instruction::create_associated_token_account, AssociatedToken},
+    associated_token::AssociatedToken,
     metadata::{
         create_metadata_accounts_v3, mpl_token_metadata::types::DataV2, CreateMetadataAccountsV3,
         Metadata as Metaplex,
     },
     token::{
-        mint_to,  Mint, MintTo, Token, TokenAccount,
+        mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer,
     },
 };
-declare_program!(dynamic_amm);
+//a creator buy executed inside create, lock_duration locks the bought tokens in the lock program vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitialBuy {
+    pub sol_amount: u64,
+    pub min_tokens_out: u64,
+    pub lock_duration: Option<i64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CreateArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub curve_kind: CurveKind,
+    pub curve_params: [u64; CURVE_PARAMS_LEN],
+    pub launch_params: Option<LaunchParams>,
+    pub migration_target: MigrationTarget,
+    pub creator_lp_fees: bool,
+    pub launch_protection: Option<LaunchProtection>,
+    pub initial_buy: Option<InitialBuy>,
+    pub vesting_schedule: Option<VestingSchedule>,
+}
+
 #[event_cpi]
 #[derive(Accounts)]
 pub struct Create<'info> {
@@ -51,12 +76,28 @@ pub struct Create<'info> {
     )]
     bonding_curve_token_account: Box<Account<'info, TokenAccount>>,
 
+    //shared by all of the creator's launches, trades accrue the creator fee into it
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CreatorVault::INIT_SPACE,
+        seeds = [CreatorVault::SEED_PREFIX, creator.key().as_ref()],
+        bump,
+    )]
+    creator_vault: Box<Account<'info, CreatorVault>>,
+
     #[account(
         seeds = [Global::SEED_PREFIX],
         bump,
     )]
     global: Box<Account<'info, Global>>,
 
+    #[account(
+        seeds = [CustomCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    custom_curve: Option<Account<'info, CustomCurve>>,
+
     ///CHECK: Using seed to validate metadata account
     #[account(
         mut,
@@ -70,6 +111,42 @@ pub struct Create<'info> {
     )]
     metadata: AccountInfo<'info>,
 
+    //the accounts below are only needed for an initial buy
+    /// CHECK: Using global state to validate fee_recipient account
+    #[account(
+        mut,
+        address = global.fee_recipient,
+    )]
+    fee_recipient: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+    )]
+    creator_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: Using global state to validate lock_program account
+    #[account(address = global.lock_program)]
+    lock_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The creator's vault in the lock program, derived in the handler
+    #[account(mut)]
+    lock_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Lock position created by the lock program, seeded by the vault's next nonce
+    #[account(mut)]
+    lock_position: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = lock_vault,
+    )]
+    lock_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
     system_program: Program<'info, System>,
 
     token_program: Program<'info, Token>,
@@ -82,14 +159,70 @@ pub struct Create<'info> {
 }
 
 
-pub fn create(ctx: Context<Create>, name: String, symbol: String, uri: String) -> Result<()> {
+pub fn create(mut ctx: Context<Create>, args: CreateArgs) -> Result<()> {
+    let CreateArgs {
+        name,
+        symbol,
+        uri,
+        curve_kind,
+        curve_params,
+        launch_params,
+        migration_target,
+        creator_lp_fees,
+        launch_protection,
+        initial_buy,
+        vesting_schedule,
+    } = args;
+
     //confirm program is initialized
     require!(
         ctx.accounts.global.initialized,
         CurveLaunchpadError::NotInitialized
     );
 
-    msg!("create::BondingCurve::get_lamports: {:?}", &ctx.accounts.bonding_curve.get_lamports());
+    //custom launch params must stay inside the bounds set by the authority
+    let launch_params = match launch_params {
+        Some(launch_params) => {
+            require!(
+                launch_params.is_within(
+                    &ctx.accounts.global.min_launch_params,
+                    &ctx.accounts.global.max_launch_params
+                ),
+                CurveLaunchpadError::InvalidLaunchParams
+            );
+            launch_params
+        }
+        None => ctx.accounts.global.default_launch_params(),
+    };
+    require!(
+        launch_params.is_consistent(),
+        CurveLaunchpadError::InvalidLaunchParams
+    );
+
+    if let Some(vesting_schedule) = vesting_schedule {
+        require!(
+            vesting_schedule.is_valid(),
+            CurveLaunchpadError::InvalidVestingSchedule
+        );
+    }
+
+    //a lock the lock program would reject must fail before anything is created
+    if let Some(lock_duration) = initial_buy.and_then(|initial_buy| initial_buy.lock_duration) {
+        require!(
+            (MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&lock_duration),
+            CurveLaunchpadError::InvalidInitialBuyLock
+        );
+    }
+
+    //a start in the past opens trading at create, the protected window then runs from now
+    let mut launch_protection = launch_protection.unwrap_or_default();
+    require!(
+        launch_protection.protected_duration >= 0,
+        CurveLaunchpadError::InvalidLaunchProtection
+    );
+    launch_protection.start_timestamp = launch_protection
+        .start_timestamp
+        .max(Clock::get()?.unix_timestamp);
 
     let seeds = &["mint-authority".as_bytes(), &[ctx.bumps.mint_authority]];
     let signer = [&seeds[..]];
@@ -131,7 +264,7 @@ pub fn create(ctx: Context<Create>, name: String, symbol: String, uri: String) -
             },
             &signer,
         ),
-        ctx.accounts.global.initial_token_supply,
+        launch_params.initial_token_supply,
     )?;
 
     //remove mint_authority
@@ -145,13 +278,48 @@ pub fn create(ctx: Context<Create>, name: String, symbol: String, uri: String) -
     );
     token::set_authority(cpi_context, AuthorityType::MintTokens, None)?;
     */
+    ctx.accounts.creator_vault.creator = ctx.accounts.creator.key();
+
     let bonding_curve = &mut ctx.accounts.bonding_curve;
-    bonding_curve.virtual_sol_reserves = ctx.accounts.global.initial_virtual_sol_reserves;
-    bonding_curve.virtual_token_reserves = ctx.accounts.global.initial_virtual_token_reserves;
+    bonding_curve.virtual_sol_reserves = launch_params.initial_virtual_sol_reserves;
+    bonding_curve.virtual_token_reserves = launch_params.initial_virtual_token_reserves;
     bonding_curve.real_sol_reserves = 0;
-    bonding_curve.real_token_reserves = ctx.accounts.global.initial_real_token_reserves;
-    bonding_curve.token_total_supply = ctx.accounts.global.initial_token_supply;
+    bonding_curve.real_token_reserves = launch_params.initial_real_token_reserves;
+    bonding_curve.token_total_supply = launch_params.initial_token_supply;
     bonding_curve.complete = false;
+    bonding_curve.migration_target = migration_target;
+    bonding_curve.creator = *ctx.accounts.creator.to_account_info().key;
+    bonding_curve.creator_lp_fees = creator_lp_fees;
+    bonding_curve.launch_protection = launch_protection;
+    bonding_curve.vesting_schedule = vesting_schedule;
+    bonding_curve.trustless_withdraw = ctx.accounts.global.trustless_withdraw;
+    bonding_curve.operator_fee_basis_points = ctx.accounts.global.operator_fee_basis_points;
+    bonding_curve.migration_fee = ctx.accounts.global.migration_fee;
+    bonding_curve.curve_kind = curve_kind;
+    bonding_curve.curve_params = curve_params;
+    bonding_curve.initial_real_token_reserves = launch_params.initial_real_token_reserves;
+    bonding_curve.initial_virtual_token_reserves = launch_params.initial_virtual_token_reserves;
+
+    //the whole curve must be priceable, otherwise the last buys would fail
+    let curve_model = bonding_curve.model(ctx.accounts.custom_curve.as_deref())?;
+    let full_curve_cost = curve_model
+        .buy_quote(bonding_curve.real_token_reserves as u128)
+        .ok_or(CurveLaunchpadError::InvalidCurveParams)?;
+    require!(
+        full_curve_cost <= u64::MAX as u128,
+        CurveLaunchpadError::InvalidCurveParams
+    );
+
+    //a completed curve must have SOL left for the pool after the migration and operator fees
+    let completion_sol = full_curve_cost as u64;
+    let completion_fees = bonding_curve
+        .migration_fee
+        .checked_add(calculate_fee(completion_sol, bonding_curve.operator_fee_basis_points))
+        .ok_or(CurveLaunchpadError::CalculationError)?;
+    require!(
+        completion_sol > completion_fees,
+        CurveLaunchpadError::InsufficientCompletionSol
+    );
 
     emit_cpi!(CreateEvent {
         name,
@@ -160,198 +328,190 @@ pub fn create(ctx: Context<Create>, name: String, symbol: String, uri: String) -
         mint: *ctx.accounts.mint.to_account_info().key,
         bonding_curve: *ctx.accounts.bonding_curve.to_account_info().key,
         creator: *ctx.accounts.creator.to_account_info().key,
+        launch_protection,
     });
 
-    Ok(())
-}
-pub fn create_permissionless_constant_product_pool_with_config(ctx: Context<CreatePermissionlessConstantProductPoolWithConfig>) -> Result<()> {
-    let accounts = dynamic_amm::cpi::accounts::InitializePermissionlessConstantProductPoolWithConfig {
-        pool: ctx.accounts.pool.to_account_info(),
-        config: ctx.accounts.config.to_account_info(),
-        lp_mint: ctx.accounts.lp_mint.to_account_info(),
-        token_a_mint: ctx.accounts.token_a_mint.to_account_info(),
-        token_b_mint: ctx.accounts.token_b_mint.to_account_info(),
-        a_vault: ctx.accounts.a_vault.to_account_info(),
-        b_vault: ctx.accounts.b_vault.to_account_info(),
-        a_token_vault: ctx.accounts.a_token_vault.to_account_info(),
-        b_token_vault: ctx.accounts.b_token_vault.to_account_info(),
-        a_vault_lp_mint: ctx.accounts.a_vault_lp_mint.to_account_info(),
-        b_vault_lp_mint: ctx.accounts.b_vault_lp_mint.to_account_info(),
-        a_vault_lp: ctx.accounts.a_vault_lp.to_account_info(),
-        b_vault_lp: ctx.accounts.b_vault_lp.to_account_info(),
-        payer_token_a: ctx.accounts.payer_token_a.to_account_info(),
-        payer_token_b: ctx.accounts.payer_token_b.to_account_info(),
-        payer_pool_lp: ctx.accounts.payer_pool_lp.to_account_info(),
-        protocol_token_a_fee: ctx.accounts.protocol_token_a_fee.to_account_info(),
-        protocol_token_b_fee: ctx.accounts.protocol_token_b_fee.to_account_info(),
-        payer: ctx.accounts.payer.to_account_info(),
-        rent: ctx.accounts.rent.to_account_info(),
-        mint_metadata: ctx.accounts.mint_metadata.to_account_info(),
-        metadata_program: ctx.accounts.metadata_program.to_account_info(),
-        vault_program: ctx.accounts.vault_program.to_account_info(),
-        token_program: ctx.accounts.token_program.to_account_info(),
-        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-    };
-    let seeds = [BondingCurve::SEED_PREFIX, ctx.accounts.token_b_mint.to_account_info().key.as_ref(), &[ctx.bumps.bonding_curve]];
-    let signer = [&seeds[..]];
-
-    let cpi_context = CpiContext::new_with_signer(ctx.accounts.dynamic_amm_program.to_account_info(), accounts, &signer);
-
-    dynamic_amm::cpi::initialize_permissionless_constant_product_pool_with_config(cpi_context, ctx.accounts.payer_token_a.amount, ctx.accounts.payer_token_b.amount)?;
-    let accounts = dynamic_amm::cpi::accounts::CreateLockEscrow {
-        lock_escrow: ctx.accounts.lock_escrow.to_account_info(),
-        payer: ctx.accounts.payer.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-        pool: ctx.accounts.pool.to_account_info(),
-        owner: ctx.accounts.payer.to_account_info(),
-        lp_mint: ctx.accounts.lp_mint.to_account_info(),
-    };
-    let cpi_context = CpiContext::new_with_signer(ctx.accounts.vault_program.to_account_info(), accounts, &signer);
-    dynamic_amm::cpi::create_lock_escrow(cpi_context)?;
-    let accounts = create_associated_token_account(ctx.accounts.payer.to_account_info().key, ctx.accounts.lock_escrow.to_account_info().key, ctx.accounts.lp_mint.to_account_info().key, ctx.accounts.token_program.to_account_info().key);
-    
-    invoke(&accounts, &
-    [
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.lock_escrow.to_account_info(),
-        ctx.accounts.lp_mint.to_account_info(),
-        ctx.accounts.lock_escrow_token_account.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-    ]
-    )?;
+    if let Some(initial_buy) = initial_buy {
+        execute_initial_buy(&mut ctx, initial_buy)?;
+    }
 
-    let accounts = dynamic_amm::cpi::accounts::Lock {
-        pool: ctx.accounts.pool.to_account_info(),
-        lp_mint: ctx.accounts.lp_mint.to_account_info(),
-        lock_escrow: ctx.accounts.lock_escrow.to_account_info(),
-        owner: ctx.accounts.payer.to_account_info(),
-        source_tokens: ctx.accounts.payer_token_a.to_account_info(),
-        escrow_vault: ctx.accounts.lock_escrow_token_account.to_account_info(),
-        token_program: ctx.accounts.token_program.to_account_info(),
-        a_vault: ctx.accounts.a_vault.to_account_info(),
-        b_vault: ctx.accounts.b_vault.to_account_info(),
-            a_vault_lp: ctx.accounts.a_vault_lp.to_account_info(),
-        b_vault_lp: ctx.accounts.b_vault_lp.to_account_info(),
-        a_vault_lp_mint: ctx.accounts.a_vault_lp_mint.to_account_info(),
-        b_vault_lp_mint: ctx.accounts.b_vault_lp_mint.to_account_info(),
-    };
-    let cpi_context = CpiContext::new_with_signer(ctx.accounts.vault_program.to_account_info(), accounts, &signer);
-    dynamic_amm::cpi::lock(cpi_context, ctx.accounts.payer_token_a.amount)?;
     Ok(())
 }
-#[derive(Accounts)]
-pub struct CreatePermissionlessConstantProductPoolWithConfig<'info> {
-    #[account(mut)]
-    pub pool: UncheckedAccount<'info>,
 
-    pub config: UncheckedAccount<'info>,
-
-    /// LP token mint of the pool
-    #[account(
-       mut
-    )]
-    pub lp_mint: UncheckedAccount<'info>,
-
-    /// Token A mint of the pool. Eg: USDT
-    pub token_a_mint: Box<Account<'info, Mint>>,
-    /// Token B mint of the pool. Eg: USDC
-    pub token_b_mint: Box<Account<'info, Mint>>,
+//the creator buys at the initial price before anyone else can trade, launch protection doesn't apply
+fn execute_initial_buy(ctx: &mut Context<Create>, initial_buy: InitialBuy) -> Result<()> {
+    let fee_recipient = ctx
+        .accounts
+        .fee_recipient
+        .as_ref()
+        .ok_or(CurveLaunchpadError::InvalidFeeRecipient)?;
+    let creator_token_account = ctx
+        .accounts
+        .creator_token_account
+        .as_ref()
+        .ok_or(CurveLaunchpadError::InitialBuyAccountsRequired)?;
+
+    //the creator share is waived, the creator would only pay it to themselves
+    let global = &ctx.accounts.global;
+    let protocol_fee_basis_points = global.protocol_fee_basis_points(0, initial_buy.sol_amount);
+    let fee_basis_points = protocol_fee_basis_points.saturating_add(global.referrer_fee_basis_points);
+
+    let mut curve_model = ctx
+        .accounts
+        .bonding_curve
+        .model(ctx.accounts.custom_curve.as_deref())?;
+    let tokens_for_sol = curve_model
+        .tokens_for_sol(initial_buy.sol_amount as u128, fee_basis_points as u128)
+        .ok_or(CurveLaunchpadError::MinBuy)?;
+    let token_amount = (tokens_for_sol as u64).min(ctx.accounts.bonding_curve.real_token_reserves);
+
+    require!(token_amount > 0, CurveLaunchpadError::MinBuy);
+    require!(
+        token_amount >= initial_buy.min_tokens_out,
+        CurveLaunchpadError::MinTokenOutputExceeded
+    );
 
-    #[account(mut)]
-    /// CHECK: Vault account for token A. Token A of the pool will be deposit / withdraw from this vault account.
-    pub a_vault: UncheckedAccount<'info>,
-    #[account(mut)]
-    /// CHECK: Vault account for token B. Token B of the pool will be deposit / withdraw from this vault account.
-    pub b_vault: UncheckedAccount<'info>,
+    let buy_result = curve_model
+        .apply_buy(token_amount as u128)
+        .ok_or(CurveLaunchpadError::InsufficientTokens)?;
+    let fees = split_fee(
+        buy_result.sol_amount,
+        protocol_fee_basis_points,
+        0,
+        global.referrer_fee_basis_points,
+        false,
+    );
+    require!(
+        buy_result.sol_amount + fees.total() <= initial_buy.sol_amount,
+        CurveLaunchpadError::MaxSOLCostExceeded
+    );
 
-    #[account(mut)]
-    /// Token vault account of vault A
-    pub a_token_vault: UncheckedAccount<'info>,
-    #[account(mut)]
-    /// Token vault account of vault B
-    pub b_token_vault: UncheckedAccount<'info>,
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: ctx.accounts.bonding_curve.to_account_info(),
+            },
+        ),
+        buy_result.sol_amount,
+    )?;
 
-    #[account(mut)]
-    /// LP token mint of vault A
-    pub a_vault_lp_mint: UncheckedAccount<'info>,
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: fee_recipient.to_account_info(),
+            },
+        ),
+        fees.protocol_fee,
+    )?;
 
-    #[account(mut)]
-    /// LP token mint of vault B
-    pub b_vault_lp_mint: UncheckedAccount<'info>,
-    /// LP token account of vault A. Used to receive/burn the vault LP upon deposit/withdraw from the vault.
-    #[account(
-        mut
-    )]
-    pub a_vault_lp: UncheckedAccount<'info>,
-    /// LP token account of vault B. Used to receive/burn vault LP upon deposit/withdraw from the vault.
-    #[account(
-        mut
-    )]
-    pub b_vault_lp: UncheckedAccount<'info>,
+    let signer: [&[&[u8]]; 1] = [&[
+        BondingCurve::SEED_PREFIX,
+        ctx.accounts.mint.to_account_info().key.as_ref(),
+        &[ctx.bumps.bonding_curve],
+    ]];
 
-    #[account(mut)]
-    /// Payer token account for pool token A mint. Used to bootstrap the pool with initial liquidity.
-    pub payer_token_a: Account<'info, TokenAccount>,
+    //paid out of the supply minted to the curve, like any other buy
+    transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bonding_curve_token_account.to_account_info(),
+                to: creator_token_account.to_account_info(),
+                authority: ctx.accounts.bonding_curve.to_account_info(),
+            },
+            &signer,
+        ),
+        buy_result.token_amount,
+    )?;
 
-    #[account(mut)]
-    /// Admin token account for pool token B mint. Used to bootstrap the pool with initial liquidity.
-    pub payer_token_b: Account<'info, TokenAccount>,
+    if let Some(lock_duration) = initial_buy.lock_duration {
+        lock_initial_buy(ctx, buy_result.token_amount, lock_duration)?;
+    }
 
-    /// CHECK: Payer pool LP token account. Used to receive LP during first deposit (initialize pool)
-    #[account(
-        mut
-    )]
-    pub payer_pool_lp: UncheckedAccount<'info>,
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+    bonding_curve.set_reserves(curve_model.reserves())?;
 
-    #[account(
-        mut
-    )]
-    /// Protocol fee token account for token A. Used to receive trading fee.
-    pub protocol_token_a_fee: UncheckedAccount<'info>,
+    emit_cpi!(TradeEvent {
+        mint: *ctx.accounts.mint.to_account_info().key,
+        sol_amount: buy_result.sol_amount,
+        token_amount: buy_result.token_amount,
+        is_buy: true,
+        user: *ctx.accounts.creator.to_account_info().key,
+        timestamp: Clock::get()?.unix_timestamp,
+        virtual_sol_reserves: bonding_curve.virtual_sol_reserves,
+        virtual_token_reserves: bonding_curve.virtual_token_reserves,
+        real_sol_reserves: bonding_curve.real_sol_reserves,
+        real_token_reserves: bonding_curve.real_token_reserves,
+        protocol_fee: fees.protocol_fee,
+        creator_fee: fees.creator_fee,
+        referrer_fee: fees.referrer_fee,
+        referrer: Pubkey::default(),
+        protocol_fee_basis_points,
+        fee_basis_points,
+    });
 
-    /// Protocol fee token account for token B. Used to receive trading fee.
-    #[account(
-        mut
-    )]
-    pub protocol_token_b_fee: UncheckedAccount<'info>,
+    if bonding_curve.real_token_reserves == 0 {
+        bonding_curve.complete = true;
 
-    /// Admin account. This account will be the admin of the pool, and the payer for PDA during initialize pool.
-    #[account(mut)]
-    pub payer: Signer<'info>,
+        emit_cpi!(CompleteEvent {
+            user: *ctx.accounts.creator.to_account_info().key,
+            mint: *ctx.accounts.mint.to_account_info().key,
+            bonding_curve: *ctx.accounts.bonding_curve.to_account_info().key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
 
-    /// Rent account.
-    pub rent: Sysvar<'info, Rent>,
+    Ok(())
+}
 
-    /// CHECK: LP mint metadata PDA. Metaplex do the checking.
-    #[account(mut)]
-    pub mint_metadata: UncheckedAccount<'info>,
+//moves the bought tokens into the creator's lock program vault through its lock_tokens instruction
+fn lock_initial_buy(ctx: &Context<Create>, amount: u64, lock_duration: i64) -> Result<()> {
+    let (
+        Some(lock_program),
+        Some(lock_vault),
+        Some(lock_position),
+        Some(lock_vault_token_account),
+        Some(creator_token_account),
+    ) = (
+        ctx.accounts.lock_program.as_ref(),
+        ctx.accounts.lock_vault.as_ref(),
+        ctx.accounts.lock_position.as_ref(),
+        ctx.accounts.lock_vault_token_account.as_ref(),
+        ctx.accounts.creator_token_account.as_ref(),
+    ) else {
+        return err!(CurveLaunchpadError::InitialBuyAccountsRequired);
+    };
 
-    /// CHECK: Metadata program
-    pub metadata_program: UncheckedAccount<'info>,
+    //the vault must be the creator's, otherwise the tokens would be locked for someone else
+    let creator = &ctx.accounts.creator;
+    let (expected_lock_vault, _) =
+        Pubkey::find_program_address(&[b"vault", creator.key.as_ref()], lock_program.key);
+    require!(
+        lock_vault.key() == expected_lock_vault,
+        CurveLaunchpadError::InvalidLockVault
+    );
 
-    /// CHECK: Vault program. The pool will deposit/withdraw liquidity from the vault.
-    pub vault_program: UncheckedAccount<'info>,
-    /// Token program.
-    pub token_program: Program<'info, Token>,
-    /// Associated token program.
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    /// System program.
-    pub system_program: Program<'info, System>,
+    require!(amount >= MIN_LOCK_AMOUNT, CurveLaunchpadError::InvalidInitialBuyLock);
 
-    pub dynamic_amm_program: UncheckedAccount<'info>,
+    let accounts = token_lock::cpi::accounts::LockTokens {
+        vault: lock_vault.to_account_info(),
+        lock_position: lock_position.to_account_info(),
+        user_token_account: creator_token_account.to_account_info(),
+        vault_token_account: lock_vault_token_account.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        authority: creator.to_account_info(),
+        owner: creator.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    token_lock::cpi::lock_tokens(
+        CpiContext::new(lock_program.to_account_info(), accounts),
+        amount,
+        lock_duration,
+    )?;
 
-    #[account(
-        seeds = [BondingCurve::SEED_PREFIX, token_b_mint.to_account_info().key.as_ref()],
-        bump,
-    )]
-    pub bonding_curve: Box<Account<'info, BondingCurve>>,
-    #[account(
-        mut
-    )]
-    pub lock_escrow: UncheckedAccount<'info>,
-    #[account(
-        mut
-    )]
-    pub lock_escrow_token_account: UncheckedAccount<'info>,
-}
\ No newline at end of file
+    Ok(())
+}
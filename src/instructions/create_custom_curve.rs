@@ -0,0 +1,55 @@
+use crate::{
+    state::{Breakpoint, CustomCurve, Global, MAX_BREAKPOINTS},
+    CurveLaunchpadError,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateCustomCurve<'info> {
+    //the mint keypair signs so nobody else can pick the curve for it, create initializes the mint afterwards
+    mint: Signer<'info>,
+
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [CustomCurve::SEED_PREFIX, mint.key.as_ref()],
+        bump,
+        space = 8 + CustomCurve::INIT_SPACE,
+    )]
+    custom_curve: Account<'info, CustomCurve>,
+
+    #[account(
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn create_custom_curve(ctx: Context<CreateCustomCurve>, breakpoints: Vec<Breakpoint>) -> Result<()> {
+    require!(
+        ctx.accounts.global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    require!(
+        breakpoints.len() <= MAX_BREAKPOINTS,
+        CurveLaunchpadError::InvalidCurveParams
+    );
+
+    let custom_curve = &mut ctx.accounts.custom_curve;
+    custom_curve.mint = *ctx.accounts.mint.key;
+    custom_curve.breakpoints = breakpoints;
+
+    //breakpoints have to start at zero supply and be monotone in supply and price
+    require!(
+        custom_curve.price_function().is_some(),
+        CurveLaunchpadError::InvalidCurveParams
+    );
+
+    Ok(())
+}
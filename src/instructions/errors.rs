@@ -21,6 +21,10 @@ pub enum CurveLaunchpadError {
     MaxSOLCostExceeded,
     #[msg("Min SOL Output Exceeded")]
     MinSOLOutputExceeded,
+    #[msg("Min Token Output Exceeded")]
+    MinTokenOutputExceeded,
+    #[msg("Max Token Input Exceeded")]
+    MaxTokenInputExceeded,
     #[msg("Min buy is 1 Token")]
     MinBuy,
     #[msg("Min sell is 1 Token")]
@@ -29,4 +33,64 @@ pub enum CurveLaunchpadError {
     InvalidFeeRecipient,
     #[msg("Invalid Withdraw Authority")]
     InvalidWithdrawAuthority,
+    #[msg("Invalid Curve Params")]
+    InvalidCurveParams,
+    #[msg("Invalid Launch Params")]
+    InvalidLaunchParams,
+    #[msg("Bonding Curve Already Migrated")]
+    AlreadyMigrated,
+    #[msg("Invalid Migration Target")]
+    InvalidMigrationTarget,
+    #[msg("Withdraw Disabled, Curve Must Migrate")]
+    WithdrawDisabled,
+    #[msg("Invalid Operator Fee")]
+    InvalidOperatorFee,
+    #[msg("Invalid Fee Schedule")]
+    InvalidFeeSchedule,
+    #[msg("Invalid Referrer")]
+    InvalidReferrer,
+    #[msg("Nothing To Claim")]
+    NothingToClaim,
+    #[msg("Bonding Curve Not Migrated")]
+    NotMigrated,
+    #[msg("Invalid Launch Protection")]
+    InvalidLaunchProtection,
+    #[msg("Trading Not Started")]
+    TradingNotStarted,
+    #[msg("Max Tokens Per Transaction Exceeded")]
+    MaxTokensPerTransactionExceeded,
+    #[msg("Max Wallet Buy Exceeded")]
+    MaxWalletBuyExceeded,
+    #[msg("Wallet Purchase Account Required")]
+    WalletPurchaseRequired,
+    #[msg("Invalid Allowlist")]
+    InvalidAllowlist,
+    #[msg("Allowlist Phase Already Started")]
+    AllowlistStarted,
+    #[msg("Invalid Allowlist Proof")]
+    InvalidAllowlistProof,
+    #[msg("Allowlist Allocation Exceeded")]
+    AllowlistAllocationExceeded,
+    #[msg("Initial Buy Accounts Required")]
+    InitialBuyAccountsRequired,
+    #[msg("Invalid Lock Vault")]
+    InvalidLockVault,
+    #[msg("Invalid Vesting Schedule")]
+    InvalidVestingSchedule,
+    #[msg("Vesting Disabled For This Launch")]
+    VestingDisabled,
+    #[msg("Vesting Accounts Required")]
+    VestingAccountsRequired,
+    #[msg("Invalid Migration Config")]
+    InvalidMigrationConfig,
+    #[msg("Trading Already Started")]
+    TradingStarted,
+    #[msg("Invalid Migration Fee")]
+    InvalidMigrationFee,
+    #[msg("Curve Completion Doesn't Cover The Migration Fees")]
+    InsufficientCompletionSol,
+    #[msg("Invalid Initial Buy Lock")]
+    InvalidInitialBuyLock,
+    #[msg("Calculation Error")]
+    CalculationError,
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{FeeTier, LaunchParams, LaunchProtection, MigrationTarget};
+
 #[event]
 pub struct CreateEvent {
     pub name: String,
@@ -8,6 +10,7 @@ pub struct CreateEvent {
     pub mint: Pubkey,
     pub bonding_curve: Pubkey,
     pub creator: Pubkey,
+    pub launch_protection: LaunchProtection,
 }
 
 #[event]
@@ -22,6 +25,13 @@ pub struct TradeEvent {
     pub virtual_token_reserves: u64,
     pub real_sol_reserves: u64,
     pub real_token_reserves: u64,
+    pub protocol_fee: u64,
+    pub creator_fee: u64,
+    pub referrer_fee: u64,
+    pub referrer: Pubkey,
+    //protocol share picked from the fee tiers, and the total charged on the trade
+    pub protocol_fee_basis_points: u64,
+    pub fee_basis_points: u64,
 }
 
 #[event]
@@ -41,4 +51,121 @@ pub struct SetParamsEvent {
     pub initial_real_token_reserves: u64,
     pub initial_token_supply: u64,
     pub fee_basis_points: u64,
-}
\ No newline at end of file
+    pub migration_fee: u64,
+}
+
+#[event]
+pub struct SetLaunchParamBoundsEvent {
+    pub min_launch_params: LaunchParams,
+    pub max_launch_params: LaunchParams,
+}
+
+#[event]
+pub struct MigrationEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub pool: Pubkey,
+    pub lock_escrow: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub lp_amount: u64,
+    pub migration_fee: u64,
+    pub operator_fee: u64,
+    pub target: MigrationTarget,
+    pub lp_burned: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SetWithdrawModeEvent {
+    pub trustless_withdraw: bool,
+    pub operator_fee_basis_points: u64,
+}
+
+#[event]
+pub struct SetLockProgramEvent {
+    pub lock_program: Pubkey,
+}
+
+#[event]
+pub struct SetMigrationConfigEvent {
+    pub dynamic_amm_config: Pubkey,
+}
+
+#[event]
+pub struct SetFeeScheduleEvent {
+    pub protocol_fee_basis_points: u64,
+    pub creator_fee_basis_points: u64,
+    pub referrer_fee_basis_points: u64,
+    pub creator_lp_fee_basis_points: u64,
+}
+
+#[event]
+pub struct SetFeeTiersEvent {
+    pub progress_fee_tiers: Vec<FeeTier>,
+    pub size_fee_tiers: Vec<FeeTier>,
+}
+
+#[event]
+pub struct ReferrerRegisteredEvent {
+    pub owner: Pubkey,
+    pub fee_vault: Pubkey,
+}
+
+#[event]
+pub struct FeesClaimedEvent {
+    pub owner: Pubkey,
+    pub fee_vault: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorFeesClaimedEvent {
+    pub creator: Pubkey,
+    pub creator_vault: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpFeesClaimedEvent {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub pool: Pubkey,
+    pub protocol_token_fee: u64,
+    pub protocol_wsol_fee: u64,
+    pub creator_token_fee: u64,
+    pub creator_wsol_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SetAllowlistEvent {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub start_timestamp: i64,
+    pub public_timestamp: i64,
+}
+
+#[event]
+pub struct BuyVestedEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub buy_vesting: Pubkey,
+    pub amount: u64,
+    pub total_amount: u64,
+    pub start_timestamp: i64,
+}
+
+#[event]
+pub struct BuyVestingClaimedEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub buy_vesting: Pubkey,
+    pub amount: u64,
+    pub released_amount: u64,
+    pub timestamp: i64,
+}
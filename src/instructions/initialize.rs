@@ -32,11 +32,16 @@ pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     global.initialized = true;
     global.initial_token_supply = DEFAULT_TOKEN_SUPPLY;
     global.initial_real_sol_reserves = 0;
-    global.initial_real_token_reserves = DEFAULT_TOKEN_SUPPLY;
+    //the rest of the supply stays on the curve to seed the pool at migration
+    global.initial_real_token_reserves = 793_100_000_000_000;
     global.initial_virtual_sol_reserves = 30_000_000_000;
     global.initial_virtual_token_reserves = 1_073_000_000_000_000;
     global.fee_basis_points = 50;
 
+    //per-launch params are pinned to the defaults until the authority widens the bounds
+    global.min_launch_params = global.default_launch_params();
+    global.max_launch_params = global.default_launch_params();
+
     msg!("Initialized global state");
 
     Ok(())
@@ -0,0 +1,477 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address, AssociatedToken},
+    token::{self, accessor, spl_token::native_mint, Mint, SyncNative, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    dynamic_amm,
+    state::{BondingCurve, Global, MigrationTarget},
+    calculate_fee, CurveLaunchpadError, MigrationEvent, POOL_CREATION_LAMPORTS,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    /// CHECK: Using global state to validate fee_recipient account
+    #[account(mut)]
+    fee_recipient: AccountInfo<'info>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(address = native_mint::ID)]
+    wsol_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [BondingCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bonding_curve,
+    )]
+    bonding_curve_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: LP account of the bonding curve, created here once the pool's lp_mint exists
+    #[account(
+        mut,
+        address = get_associated_token_address(bonding_curve.to_account_info().key, lp_mint.key),
+    )]
+    bonding_curve_lp_account: UncheckedAccount<'info>,
+
+    /// CHECK: Using seed to validate pool_authority account, it pays for and creates the pool
+    #[account(
+        mut,
+        seeds = [b"pool-authority"],
+        bump,
+    )]
+    pool_authority: UncheckedAccount<'info>,
+
+    //liquidity passes through the pool authority's accounts because dynamic_amm pulls it from the pool payer
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = pool_authority,
+    )]
+    pool_authority_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = pool_authority,
+    )]
+    pool_authority_wsol_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Receives the pool LP, created by dynamic_amm
+    #[account(mut)]
+    pool_authority_lp: UncheckedAccount<'info>,
+
+    /// CHECK: Derived and initialized by dynamic_amm from the config and both mints
+    #[account(mut)]
+    pool: UncheckedAccount<'info>,
+
+    //pools for the config can only be created by its pool creator authority, so the pool address
+    //can't be taken before the curve migrates
+    #[account(
+        address = global.dynamic_amm_config @ CurveLaunchpadError::InvalidMigrationConfig,
+        constraint = config.pool_creator_authority == pool_authority.key() @ CurveLaunchpadError::InvalidMigrationConfig,
+    )]
+    config: Box<Account<'info, dynamic_amm::accounts::Config>>,
+
+    /// CHECK: LP token mint of the pool, initialized by dynamic_amm
+    #[account(mut)]
+    lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Vault account for the launched token
+    #[account(mut)]
+    a_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Vault account for WSOL
+    #[account(mut)]
+    b_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Token vault account of vault A
+    #[account(mut)]
+    a_token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Token vault account of vault B
+    #[account(mut)]
+    b_token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: LP token mint of vault A
+    #[account(mut)]
+    a_vault_lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: LP token mint of vault B
+    #[account(mut)]
+    b_vault_lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: LP token account of vault A, owned by the pool
+    #[account(mut)]
+    a_vault_lp: UncheckedAccount<'info>,
+
+    /// CHECK: LP token account of vault B, owned by the pool
+    #[account(mut)]
+    b_vault_lp: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol fee token account for token A
+    #[account(mut)]
+    protocol_token_a_fee: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol fee token account for token B
+    #[account(mut)]
+    protocol_token_b_fee: UncheckedAccount<'info>,
+
+    /// CHECK: LP mint metadata PDA, checked by the metadata program
+    #[account(mut)]
+    mint_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Lock escrow of the bonding curve for this pool
+    #[account(
+        mut,
+        seeds = [b"lock_escrow", pool.key.as_ref(), bonding_curve.to_account_info().key.as_ref()],
+        bump,
+        seeds::program = dynamic_amm_program.key(),
+    )]
+    lock_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: LP account of the lock escrow, created here
+    #[account(
+        mut,
+        address = get_associated_token_address(lock_escrow.key, lp_mint.key),
+    )]
+    escrow_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Metadata program
+    metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Vault program, the pool deposits its liquidity through it
+    vault_program: UncheckedAccount<'info>,
+
+    dynamic_amm_program: Program<'info, dynamic_amm::program::DynamicAmm>,
+
+    rent: Sysvar<'info, Rent>,
+
+    token_program: Program<'info, Token>,
+
+    associated_token_program: Program<'info, AssociatedToken>,
+
+    system_program: Program<'info, System>,
+}
+
+pub struct MigrationAmounts {
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub migration_fee: u64,
+    pub operator_fee: u64,
+}
+
+//accounts every migration target needs to empty the curve into the accounts the pool is seeded from
+pub struct CurveLiquidity<'a, 'info> {
+    pub global: &'a Account<'info, Global>,
+    pub fee_recipient: &'a AccountInfo<'info>,
+    pub bonding_curve: &'a Account<'info, BondingCurve>,
+    pub bonding_curve_token_account: &'a Account<'info, TokenAccount>,
+    pub liquidity_token_account: &'a Account<'info, TokenAccount>,
+    pub liquidity_wsol_account: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+impl<'a, 'info> CurveLiquidity<'a, 'info> {
+    //checks the curve can migrate to target, pays the migration fee and moves the remaining
+    //tokens and real SOL (as WSOL) to the liquidity accounts the pool is seeded from
+    pub fn release(&self, target: MigrationTarget, signer: &[&[&[u8]]]) -> Result<MigrationAmounts> {
+        require!(
+            self.global.initialized,
+            CurveLaunchpadError::NotInitialized
+        );
+
+        require!(
+            self.bonding_curve.complete,
+            CurveLaunchpadError::BondingCurveNotComplete,
+        );
+
+        require!(
+            !self.bonding_curve.migrated,
+            CurveLaunchpadError::AlreadyMigrated,
+        );
+
+        require!(
+            self.bonding_curve.migration_target == target,
+            CurveLaunchpadError::InvalidMigrationTarget,
+        );
+
+        //invalid fee recipient
+        require!(
+            self.fee_recipient.key == &self.global.fee_recipient,
+            CurveLaunchpadError::InvalidFeeRecipient,
+        );
+
+        //everything above rent is curve SOL, but never more than the curve accounts for
+        let min_balance = Rent::get()?.minimum_balance(8 + BondingCurve::INIT_SPACE);
+        let available_lamports = self
+            .bonding_curve
+            .get_lamports()
+            .checked_sub(min_balance)
+            .ok_or(CurveLaunchpadError::InsufficientSOL)?;
+        let real_sol_reserves = self.bonding_curve.real_sol_reserves.min(available_lamports);
+
+        //both fees are the ones recorded on the curve at create, not the current global values
+        let migration_fee = self.bonding_curve.migration_fee;
+        let operator_fee = calculate_fee(real_sol_reserves, self.bonding_curve.operator_fee_basis_points);
+        let total_fee = migration_fee
+            .checked_add(operator_fee)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+        require!(
+            real_sol_reserves > total_fee,
+            CurveLaunchpadError::InsufficientSOL,
+        );
+        let sol_amount = real_sol_reserves - total_fee;
+
+        let token_amount = self.bonding_curve_token_account.amount;
+        require!(token_amount > 0, CurveLaunchpadError::InsufficientTokens);
+
+        //transfer migration and operator fees to fee recipient
+        let bonding_curve_info = self.bonding_curve.to_account_info();
+        **bonding_curve_info.try_borrow_mut_lamports()? -= total_fee;
+        **self.fee_recipient.try_borrow_mut_lamports()? += total_fee;
+
+        //wrap the curve SOL into the liquidity WSOL account
+        **bonding_curve_info.try_borrow_mut_lamports()? -= sol_amount;
+        **self.liquidity_wsol_account.to_account_info().try_borrow_mut_lamports()? += sol_amount;
+
+        token::sync_native(CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative {
+                account: self.liquidity_wsol_account.to_account_info(),
+            },
+        ))?;
+
+        //transfer the remaining tokens to the liquidity token account
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.bonding_curve_token_account.to_account_info(),
+                    to: self.liquidity_token_account.to_account_info(),
+                    authority: self.bonding_curve.to_account_info(),
+                },
+                signer,
+            ),
+            token_amount,
+        )?;
+
+        Ok(MigrationAmounts {
+            sol_amount,
+            token_amount,
+            migration_fee,
+            operator_fee,
+        })
+    }
+}
+
+pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+    let mint_key = ctx.accounts.mint.key();
+    let signer: [&[&[u8]]; 1] = [&[
+        BondingCurve::SEED_PREFIX,
+        mint_key.as_ref(),
+        &[ctx.bumps.bonding_curve],
+    ]];
+
+    let MigrationAmounts {
+        sol_amount,
+        token_amount,
+        migration_fee,
+        operator_fee,
+    } = CurveLiquidity {
+        global: &ctx.accounts.global,
+        fee_recipient: &ctx.accounts.fee_recipient,
+        bonding_curve: &ctx.accounts.bonding_curve,
+        bonding_curve_token_account: &ctx.accounts.bonding_curve_token_account,
+        liquidity_token_account: &ctx.accounts.pool_authority_token_account,
+        liquidity_wsol_account: &ctx.accounts.pool_authority_wsol_account,
+        token_program: &ctx.accounts.token_program,
+    }
+    .release(MigrationTarget::DynamicAmm, &signer)?;
+
+    //the pool authority pays the pool's rent, what it doesn't use goes back to the payer below
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.pool_authority.to_account_info(),
+            },
+        ),
+        POOL_CREATION_LAMPORTS,
+    )?;
+
+    let pool_authority_signer: [&[&[u8]]; 1] = [&[b"pool-authority", &[ctx.bumps.pool_authority]]];
+
+    //create the pool seeded with exactly what left the curve
+    let accounts = dynamic_amm::cpi::accounts::InitializePermissionlessConstantProductPoolWithConfig {
+        pool: ctx.accounts.pool.to_account_info(),
+        config: ctx.accounts.config.to_account_info(),
+        lp_mint: ctx.accounts.lp_mint.to_account_info(),
+        token_a_mint: ctx.accounts.mint.to_account_info(),
+        token_b_mint: ctx.accounts.wsol_mint.to_account_info(),
+        a_vault: ctx.accounts.a_vault.to_account_info(),
+        b_vault: ctx.accounts.b_vault.to_account_info(),
+        a_token_vault: ctx.accounts.a_token_vault.to_account_info(),
+        b_token_vault: ctx.accounts.b_token_vault.to_account_info(),
+        a_vault_lp_mint: ctx.accounts.a_vault_lp_mint.to_account_info(),
+        b_vault_lp_mint: ctx.accounts.b_vault_lp_mint.to_account_info(),
+        a_vault_lp: ctx.accounts.a_vault_lp.to_account_info(),
+        b_vault_lp: ctx.accounts.b_vault_lp.to_account_info(),
+        payer_token_a: ctx.accounts.pool_authority_token_account.to_account_info(),
+        payer_token_b: ctx.accounts.pool_authority_wsol_account.to_account_info(),
+        payer_pool_lp: ctx.accounts.pool_authority_lp.to_account_info(),
+        protocol_token_a_fee: ctx.accounts.protocol_token_a_fee.to_account_info(),
+        protocol_token_b_fee: ctx.accounts.protocol_token_b_fee.to_account_info(),
+        payer: ctx.accounts.pool_authority.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+        mint_metadata: ctx.accounts.mint_metadata.to_account_info(),
+        metadata_program: ctx.accounts.metadata_program.to_account_info(),
+        vault_program: ctx.accounts.vault_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    dynamic_amm::cpi::initialize_permissionless_constant_product_pool_with_config(
+        CpiContext::new_with_signer(
+            ctx.accounts.dynamic_amm_program.to_account_info(),
+            accounts,
+            &pool_authority_signer,
+        ),
+        token_amount,
+        sol_amount,
+    )?;
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.pool_authority.to_account_info(),
+                to: ctx.accounts.payer.to_account_info(),
+            },
+            &pool_authority_signer,
+        ),
+        ctx.accounts.pool_authority.lamports(),
+    )?;
+
+    //the lock escrow must be owned by the bonding curve, so the LP moves to it first
+    let lp_amount = accessor::amount(&ctx.accounts.pool_authority_lp.to_account_info())?;
+
+    associated_token::create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: ctx.accounts.payer.to_account_info(),
+            associated_token: ctx.accounts.bonding_curve_lp_account.to_account_info(),
+            authority: ctx.accounts.bonding_curve.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_authority_lp.to_account_info(),
+                to: ctx.accounts.bonding_curve_lp_account.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &pool_authority_signer,
+        ),
+        lp_amount,
+    )?;
+
+    let accounts = dynamic_amm::cpi::accounts::CreateLockEscrow {
+        pool: ctx.accounts.pool.to_account_info(),
+        lock_escrow: ctx.accounts.lock_escrow.to_account_info(),
+        owner: ctx.accounts.bonding_curve.to_account_info(),
+        lp_mint: ctx.accounts.lp_mint.to_account_info(),
+        payer: ctx.accounts.payer.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    dynamic_amm::cpi::create_lock_escrow(CpiContext::new(
+        ctx.accounts.dynamic_amm_program.to_account_info(),
+        accounts,
+    ))?;
+
+    associated_token::create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: ctx.accounts.payer.to_account_info(),
+            associated_token: ctx.accounts.escrow_vault.to_account_info(),
+            authority: ctx.accounts.lock_escrow.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    let accounts = dynamic_amm::cpi::accounts::Lock {
+        pool: ctx.accounts.pool.to_account_info(),
+        lp_mint: ctx.accounts.lp_mint.to_account_info(),
+        lock_escrow: ctx.accounts.lock_escrow.to_account_info(),
+        owner: ctx.accounts.bonding_curve.to_account_info(),
+        source_tokens: ctx.accounts.bonding_curve_lp_account.to_account_info(),
+        escrow_vault: ctx.accounts.escrow_vault.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        a_vault: ctx.accounts.a_vault.to_account_info(),
+        b_vault: ctx.accounts.b_vault.to_account_info(),
+        a_vault_lp: ctx.accounts.a_vault_lp.to_account_info(),
+        b_vault_lp: ctx.accounts.b_vault_lp.to_account_info(),
+        a_vault_lp_mint: ctx.accounts.a_vault_lp_mint.to_account_info(),
+        b_vault_lp_mint: ctx.accounts.b_vault_lp_mint.to_account_info(),
+    };
+    dynamic_amm::cpi::lock(
+        CpiContext::new_with_signer(
+            ctx.accounts.dynamic_amm_program.to_account_info(),
+            accounts,
+            &signer,
+        ),
+        lp_amount,
+    )?;
+
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+    bonding_curve.real_sol_reserves = 0;
+    bonding_curve.real_token_reserves = 0;
+    bonding_curve.migrated = true;
+    bonding_curve.pool = ctx.accounts.pool.key();
+
+    emit_cpi!(MigrationEvent {
+        user: *ctx.accounts.payer.to_account_info().key,
+        mint: mint_key,
+        bonding_curve: *ctx.accounts.bonding_curve.to_account_info().key,
+        pool: ctx.accounts.pool.key(),
+        lock_escrow: ctx.accounts.lock_escrow.key(),
+        sol_amount,
+        token_amount,
+        lp_amount,
+        migration_fee,
+        operator_fee,
+        target: MigrationTarget::DynamicAmm,
+        lp_burned: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, accessor, spl_token::native_mint, Burn, Mint, Token, TokenAccount},
+};
+
+use crate::{
+    raydium_cp_swap,
+    state::{BondingCurve, Global, MigrationTarget},
+    CurveLiquidity, MigrationAmounts, MigrationEvent,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateRaydium<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    /// CHECK: Using global state to validate fee_recipient account
+    #[account(mut)]
+    fee_recipient: AccountInfo<'info>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(address = native_mint::ID)]
+    wsol_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [BondingCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bonding_curve,
+    )]
+    bonding_curve_token_account: Box<Account<'info, TokenAccount>>,
+
+    //liquidity passes through the payer's accounts because cp-swap pulls it from the pool creator
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = payer,
+    )]
+    payer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = payer,
+    )]
+    payer_wsol_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Receives the pool LP before it is burned, created by cp-swap
+    #[account(mut)]
+    payer_lp_account: UncheckedAccount<'info>,
+
+    /// CHECK: Fee tier config, validated by cp-swap
+    amm_config: UncheckedAccount<'info>,
+
+    /// CHECK: Vault and LP mint authority PDA, validated by cp-swap
+    authority: UncheckedAccount<'info>,
+
+    /// CHECK: Initialized by cp-swap, which takes any address that signs instead of its
+    /// config and mints PDA, so the pool sits at a launchpad PDA nobody else can create
+    #[account(
+        mut,
+        seeds = [b"raydium-pool", mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    pool_state: UncheckedAccount<'info>,
+
+    /// CHECK: LP token mint of the pool, initialized by cp-swap
+    #[account(mut)]
+    lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Pool vault of the launched token, initialized by cp-swap
+    #[account(mut)]
+    token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Pool vault of WSOL, initialized by cp-swap
+    #[account(mut)]
+    wsol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Pool creation fee receiver, validated by cp-swap
+    #[account(mut)]
+    create_pool_fee: UncheckedAccount<'info>,
+
+    /// CHECK: Price observation account, initialized by cp-swap
+    #[account(mut)]
+    observation_state: UncheckedAccount<'info>,
+
+    cp_swap_program: Program<'info, raydium_cp_swap::program::RaydiumCpSwap>,
+
+    rent: Sysvar<'info, Rent>,
+
+    token_program: Program<'info, Token>,
+
+    associated_token_program: Program<'info, AssociatedToken>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn migrate_raydium(ctx: Context<MigrateRaydium>) -> Result<()> {
+    let mint_key = ctx.accounts.mint.key();
+    let signer: [&[&[u8]]; 1] = [&[
+        BondingCurve::SEED_PREFIX,
+        mint_key.as_ref(),
+        &[ctx.bumps.bonding_curve],
+    ]];
+
+    let MigrationAmounts {
+        sol_amount,
+        token_amount,
+        migration_fee,
+        operator_fee,
+    } = CurveLiquidity {
+        global: &ctx.accounts.global,
+        fee_recipient: &ctx.accounts.fee_recipient,
+        bonding_curve: &ctx.accounts.bonding_curve,
+        bonding_curve_token_account: &ctx.accounts.bonding_curve_token_account,
+        liquidity_token_account: &ctx.accounts.payer_token_account,
+        liquidity_wsol_account: &ctx.accounts.payer_wsol_account,
+        token_program: &ctx.accounts.token_program,
+    }
+    .release(MigrationTarget::RaydiumCpSwap, &signer)?;
+
+    //cp-swap requires token_0 to be the smaller mint
+    let token_is_token_0 = mint_key < ctx.accounts.wsol_mint.key();
+    let (token_0, token_1) = if token_is_token_0 {
+        (
+            (&ctx.accounts.mint, &ctx.accounts.payer_token_account, &ctx.accounts.token_vault, token_amount),
+            (&ctx.accounts.wsol_mint, &ctx.accounts.payer_wsol_account, &ctx.accounts.wsol_vault, sol_amount),
+        )
+    } else {
+        (
+            (&ctx.accounts.wsol_mint, &ctx.accounts.payer_wsol_account, &ctx.accounts.wsol_vault, sol_amount),
+            (&ctx.accounts.mint, &ctx.accounts.payer_token_account, &ctx.accounts.token_vault, token_amount),
+        )
+    };
+
+    let accounts = raydium_cp_swap::cpi::accounts::Initialize {
+        creator: ctx.accounts.payer.to_account_info(),
+        amm_config: ctx.accounts.amm_config.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+        pool_state: ctx.accounts.pool_state.to_account_info(),
+        token_0_mint: token_0.0.to_account_info(),
+        token_1_mint: token_1.0.to_account_info(),
+        lp_mint: ctx.accounts.lp_mint.to_account_info(),
+        creator_token_0: token_0.1.to_account_info(),
+        creator_token_1: token_1.1.to_account_info(),
+        creator_lp_token: ctx.accounts.payer_lp_account.to_account_info(),
+        token_0_vault: token_0.2.to_account_info(),
+        token_1_vault: token_1.2.to_account_info(),
+        create_pool_fee: ctx.accounts.create_pool_fee.to_account_info(),
+        observation_state: ctx.accounts.observation_state.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        token_0_program: ctx.accounts.token_program.to_account_info(),
+        token_1_program: ctx.accounts.token_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+    let pool_state_signer: [&[&[u8]]; 1] = [&[
+        b"raydium-pool",
+        mint_key.as_ref(),
+        &[ctx.bumps.pool_state],
+    ]];
+
+    raydium_cp_swap::cpi::initialize(
+        CpiContext::new_with_signer(
+            ctx.accounts.cp_swap_program.to_account_info(),
+            accounts,
+            &pool_state_signer,
+        ),
+        token_0.3,
+        token_1.3,
+        0,
+    )?;
+
+    //burn the LP so the liquidity can never be pulled
+    let lp_amount = accessor::amount(&ctx.accounts.payer_lp_account.to_account_info())?;
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                from: ctx.accounts.payer_lp_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        lp_amount,
+    )?;
+
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+    bonding_curve.real_sol_reserves = 0;
+    bonding_curve.real_token_reserves = 0;
+    bonding_curve.migrated = true;
+    bonding_curve.pool = ctx.accounts.pool_state.key();
+
+    emit_cpi!(MigrationEvent {
+        user: *ctx.accounts.payer.to_account_info().key,
+        mint: mint_key,
+        bonding_curve: *ctx.accounts.bonding_curve.to_account_info().key,
+        pool: ctx.accounts.pool_state.key(),
+        lock_escrow: Pubkey::default(),
+        sol_amount,
+        token_amount,
+        lp_amount,
+        migration_fee,
+        operator_fee,
+        target: MigrationTarget::RaydiumCpSwap,
+        lp_burned: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
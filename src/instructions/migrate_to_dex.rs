@@ -1,11 +0,0 @@
-use anchor_lang::prelude::*;
-
-#[derive(Accounts)]
-pub struct MigrateToDex<'info> {
-    // Add necessary accounts for DEX migration
-}
-
-pub fn handler(ctx: Context<MigrateToDex>) -> Result<()> {
-    // Implement DEX migration logic
-    Ok(())
-}
\ No newline at end of file
@@ -1,4 +1,51 @@
-pub mod initialize_launch;
-pub mod buy_tokens;
-pub mod sell_tokens;
-pub mod migrate_to_dex;
\ No newline at end of file
+pub mod initialize;
+pub mod errors;
+pub mod buy;
+pub mod sell;
+pub mod create;
+pub mod create_custom_curve;
+pub mod set_params;
+pub mod set_launch_param_bounds;
+pub mod set_withdraw_mode;
+pub mod set_fee_schedule;
+pub mod set_fee_tiers;
+pub mod set_allowlist;
+pub mod set_lock_program;
+pub mod set_migration_config;
+pub mod register_referrer;
+pub mod claim_fees;
+pub mod claim_creator_fees;
+pub mod claim_lp_fees;
+pub mod claim_buy_vesting;
+pub mod constants;
+pub mod events;
+pub mod util;
+pub mod withdraw;
+pub mod migrate;
+pub mod migrate_raydium;
+
+pub use initialize::*;
+pub use errors::*;
+pub use buy::*;
+pub use sell::*;
+pub use create::*;
+pub use create_custom_curve::*;
+pub use set_params::*;
+pub use set_launch_param_bounds::*;
+pub use set_withdraw_mode::*;
+pub use set_fee_schedule::*;
+pub use set_fee_tiers::*;
+pub use set_allowlist::*;
+pub use set_lock_program::*;
+pub use set_migration_config::*;
+pub use register_referrer::*;
+pub use claim_fees::*;
+pub use claim_creator_fees::*;
+pub use claim_lp_fees::*;
+pub use claim_buy_vesting::*;
+pub use constants::*;
+pub use events::*;
+pub use util::*;
+pub use withdraw::*;
+pub use migrate::*;
+pub use migrate_raydium::*;
\ No newline at end of file
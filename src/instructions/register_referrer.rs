@@ -0,0 +1,35 @@
+use crate::{state::FeeVault, ReferrerRegisteredEvent};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + FeeVault::INIT_SPACE,
+        seeds = [FeeVault::SEED_PREFIX, owner.key.as_ref()],
+        bump,
+    )]
+    fee_vault: Box<Account<'info, FeeVault>>,
+
+    system_program: Program<'info, System>,
+}
+
+//creates the fee vault buys and sells pass as referrer_fee_vault to pay the owner a share of the fee
+pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+    let fee_vault = &mut ctx.accounts.fee_vault;
+    fee_vault.owner = *ctx.accounts.owner.to_account_info().key;
+    fee_vault.total_accrued = 0;
+    fee_vault.total_claimed = 0;
+
+    emit_cpi!(ReferrerRegisteredEvent {
+        owner: fee_vault.owner,
+        fee_vault: fee_vault.key(),
+    });
+
+    Ok(())
+}
@@ -1,11 +1,8 @@
-use std::{char::MAX, str::FromStr};
-
 use crate::{
-    amm, calculate_fee, state::{BondingCurve, Global}, CurveLaunchpadError, TradeEvent
+    split_fee, state::{BondingCurve, CreatorVault, CustomCurve, FeeVault, Global}, CurveLaunchpadError, TradeEvent
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
-declare_program!(dynamic_amm);
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 #[event_cpi]
 #[derive(Accounts)]
 pub struct Sell<'info> {
@@ -38,6 +35,26 @@ pub struct Sell<'info> {
     )]
     bonding_curve_token_account: Box<Account<'info, TokenAccount>>,
 
+    #[account(
+        seeds = [CustomCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+    )]
+    custom_curve: Option<Account<'info, CustomCurve>>,
+
+    #[account(
+        mut,
+        seeds = [CreatorVault::SEED_PREFIX, bonding_curve.creator.as_ref()],
+        bump,
+    )]
+    creator_vault: Box<Account<'info, CreatorVault>>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX, referrer_fee_vault.owner.as_ref()],
+        bump,
+    )]
+    referrer_fee_vault: Option<Box<Account<'info, FeeVault>>>,
+
     #[account(
         mut,
         associated_token::mint = mint,
@@ -51,8 +68,29 @@ pub struct Sell<'info> {
 }
 
 pub fn sell(ctx: Context<Sell>, token_amount: u64, min_sol_output: u64) -> Result<()> {
+    sell_tokens(ctx, token_amount, min_sol_output, None)
+}
+
+fn sell_tokens(
+    ctx: Context<Sell>,
+    token_amount: u64,
+    min_sol_output: u64,
+    protocol_fee_basis_points: Option<u64>,
+) -> Result<()> {
     //check if bonding curve is complete
-    
+    require!(
+        !ctx.accounts.bonding_curve.complete && !ctx.accounts.bonding_curve.migrated,
+        CurveLaunchpadError::BondingCurveComplete,
+    );
+
+    //trading is closed until the launch starts, presale buyers can't sell before public trading
+    let clock = Clock::get()?;
+    require!(
+        ctx.accounts
+            .bonding_curve
+            .is_public_trading_open(clock.unix_timestamp, clock.slot),
+        CurveLaunchpadError::TradingNotStarted,
+    );
 
     //confirm user has enough tokens
     require!(
@@ -66,27 +104,45 @@ pub fn sell(ctx: Context<Sell>, token_amount: u64, min_sol_output: u64) -> Resul
         CurveLaunchpadError::InvalidFeeRecipient,
     );
 
-    //confirm bonding curve has enough tokens
-    require!(
-        ctx.accounts.bonding_curve_token_account.amount >= token_amount,
-        CurveLaunchpadError::InsufficientTokens,
-    );
-
     require!(token_amount > 0, CurveLaunchpadError::MinSell,);
 
-    let mut amm = amm::amm::AMM::new(
-        ctx.accounts.bonding_curve.virtual_sol_reserves as u128,
-        ctx.accounts.bonding_curve.virtual_token_reserves as u128,
-        ctx.accounts.bonding_curve.real_sol_reserves as u128,
-        ctx.accounts.bonding_curve.real_token_reserves as u128,
-        ctx.accounts.global.initial_virtual_token_reserves as u128,
+    let mut curve_model = ctx
+        .accounts
+        .bonding_curve
+        .model(ctx.accounts.custom_curve.as_deref())?;
+
+    //a referrer can't be the seller
+    if let Some(referrer_fee_vault) = &ctx.accounts.referrer_fee_vault {
+        require!(
+            referrer_fee_vault.owner != ctx.accounts.user.key(),
+            CurveLaunchpadError::InvalidReferrer,
+        );
+    }
+
+    let sell_result = curve_model
+        .apply_sell(token_amount as u128)
+        .ok_or(CurveLaunchpadError::InsufficientSOL)?;
+    //the fee tier comes from the executed SOL amount, unless the caller already quoted with one
+    let global = &ctx.accounts.global;
+    let protocol_fee_basis_points = protocol_fee_basis_points.unwrap_or_else(|| {
+        global.protocol_fee_basis_points(
+            ctx.accounts.bonding_curve.progress_basis_points(),
+            sell_result.sol_amount,
+        )
+    });
+    let fees = split_fee(
+        sell_result.sol_amount,
+        protocol_fee_basis_points,
+        global.creator_fee_basis_points,
+        global.referrer_fee_basis_points,
+        ctx.accounts.referrer_fee_vault.is_some(),
     );
 
-    let sell_result = amm.apply_sell(token_amount as u128).unwrap();
-    let fee = calculate_fee(sell_result.sol_amount, ctx.accounts.global.fee_basis_points);
-
     //the fee is subtracted from the sol amount to confirm the user minimum sol output is met
-    let sell_amount_minus_fee = sell_result.sol_amount - fee;
+    let sell_amount_minus_fee = sell_result
+        .sol_amount
+        .checked_sub(fees.total())
+        .ok_or(CurveLaunchpadError::InsufficientSOL)?;
 
     //confirm min sol output is greater than sol output
     require!(
@@ -94,18 +150,15 @@ pub fn sell(ctx: Context<Sell>, token_amount: u64, min_sol_output: u64) -> Resul
         CurveLaunchpadError::MinSOLOutputExceeded,
     );
 
-    //transfer SPL
-    let cpi_accounts = Burn {
-        from: ctx.accounts.user_token_account.to_account_info().clone(),
-        mint: ctx.accounts.mint.to_account_info().clone(),
-        authority: ctx.accounts.user.to_account_info().clone(),
-    };
-
-    token::burn(
-        CpiContext::new_with_signer(
+    //sold tokens go back to the bonding curve so its balance keeps matching the real token reserves
+    token::transfer(
+        CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            cpi_accounts,
-            &[],
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.bonding_curve_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
         ),
         sell_result.token_amount,
     )?;
@@ -115,19 +168,31 @@ pub fn sell(ctx: Context<Sell>, token_amount: u64, min_sol_output: u64) -> Resul
     let from_account = &ctx.accounts.bonding_curve;
     let to_account = &ctx.accounts.user;
 
+    //the fee comes out of the sol the curve pays, the user receives the rest
     **from_account.to_account_info().try_borrow_mut_lamports()? -= sell_result.sol_amount;
-    **to_account.try_borrow_mut_lamports()? += sell_result.sol_amount;
+    **to_account.try_borrow_mut_lamports()? += sell_amount_minus_fee;
 
     //transfer fee to fee recipient
-    **from_account.to_account_info().try_borrow_mut_lamports()? -= fee;
-    **ctx.accounts.fee_recipient.try_borrow_mut_lamports()? += fee;
-
+    **ctx.accounts.fee_recipient.try_borrow_mut_lamports()? += fees.protocol_fee;
+
+    //the creator share accrues in the creator vault, the referrer share in the referrer fee vault
+    **ctx.accounts.creator_vault.to_account_info().try_borrow_mut_lamports()? += fees.creator_fee;
+    let creator_vault = &mut ctx.accounts.creator_vault;
+    creator_vault.total_accrued = creator_vault
+        .total_accrued
+        .checked_add(fees.creator_fee)
+        .ok_or(CurveLaunchpadError::CalculationError)?;
+
+    if let Some(referrer_fee_vault) = &mut ctx.accounts.referrer_fee_vault {
+        **referrer_fee_vault.to_account_info().try_borrow_mut_lamports()? += fees.referrer_fee;
+        referrer_fee_vault.total_accrued = referrer_fee_vault
+            .total_accrued
+            .checked_add(fees.referrer_fee)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+    }
 
     let bonding_curve = &mut ctx.accounts.bonding_curve;
-    bonding_curve.real_token_reserves = amm.real_token_reserves as u64;
-    bonding_curve.real_sol_reserves = amm.real_sol_reserves as u64;
-    bonding_curve.virtual_token_reserves = amm.virtual_token_reserves as u64;
-    bonding_curve.virtual_sol_reserves = amm.virtual_sol_reserves as u64;
+    bonding_curve.set_reserves(curve_model.reserves())?;
 
     emit_cpi!(TradeEvent {
         mint: *ctx.accounts.mint.to_account_info().key,
@@ -140,115 +205,54 @@ pub fn sell(ctx: Context<Sell>, token_amount: u64, min_sol_output: u64) -> Resul
         virtual_token_reserves: bonding_curve.virtual_token_reserves,
         real_sol_reserves: bonding_curve.real_sol_reserves,
         real_token_reserves: bonding_curve.real_token_reserves,
+        protocol_fee: fees.protocol_fee,
+        creator_fee: fees.creator_fee,
+        referrer_fee: fees.referrer_fee,
+        referrer: ctx
+            .accounts
+            .referrer_fee_vault
+            .as_ref()
+            .map_or(Pubkey::default(), |referrer_fee_vault| referrer_fee_vault.owner),
+        protocol_fee_basis_points,
+        fee_basis_points: ctx.accounts.global.total_fee_basis_points(protocol_fee_basis_points),
     });
 
     Ok(())
 }
 
-pub fn claim_fee(ctx: Context<ClaimFee>) -> Result<()> {
-    let accounts = dynamic_amm::cpi::accounts::ClaimFee {
-        pool: ctx.accounts.pool.to_account_info(),
-        lp_mint: ctx.accounts.lp_mint.to_account_info(),
-        lock_escrow: ctx.accounts.lock_escrow.to_account_info(),
-        owner: ctx.accounts.bonding_curve.to_account_info(),
-        source_tokens: ctx.accounts.user_a_token.to_account_info(),
-        escrow_vault: ctx.accounts.lock_escrow.to_account_info(),
-        token_program: ctx.accounts.token_program.to_account_info(),
-        a_token_vault: ctx.accounts.a_token_vault.to_account_info(),
-        b_token_vault: ctx.accounts.b_token_vault.to_account_info(),
-        a_vault: ctx.accounts.a_vault.to_account_info(),
-        b_vault: ctx.accounts.b_vault.to_account_info(),
-        a_vault_lp: ctx.accounts.a_vault_lp.to_account_info(),
-        b_vault_lp: ctx.accounts.b_vault_lp.to_account_info(),
-        a_vault_lp_mint: ctx.accounts.a_vault_lp_mint.to_account_info(),
-        b_vault_lp_mint: ctx.accounts.b_vault_lp_mint.to_account_info(),
-        user_a_token: ctx.accounts.user_a_token.to_account_info(),
-        user_b_token: ctx.accounts.user_b_token.to_account_info(),
-        vault_program: ctx.accounts.vault_program.to_account_info(),
-    };
-    let signer: [&[&[u8]]; 1] = [&[
-        BondingCurve::SEED_PREFIX,
-        ctx.accounts.a_vault_lp_mint.to_account_info().key.as_ref(),
-        &[ctx.bumps.bonding_curve],
-    ]];
-
-
-    let cpi_context = CpiContext::new_with_signer(ctx.accounts.vault_program.to_account_info(), accounts, &signer);
-    dynamic_amm::cpi::claim_fee(cpi_context, u64::MAX)?;
-
-
-    Ok(())
-}
-
-#[derive(Accounts)]
-pub struct ClaimFee<'info> {
-    
-    /// CHECK: Pool account
-    #[account(mut)]
-    pub pool: UncheckedAccount<'info>,
-
-    /// CHECK: LP token mint of the pool
-    #[account(mut)]
-    pub lp_mint: UncheckedAccount<'info>,
-
-    /// CHECK: Lock account
-    #[account(mut)]
-    pub lock_escrow: UncheckedAccount<'info>,
-
-    /// CHECK: Owner of lock account
-    #[account(mut)]
-    pub owner: Signer<'info>,
-
-    /// CHECK: owner lp token account
-    #[account(mut)]
-    pub source_tokens: UncheckedAccount<'info>,
-
-    /// CHECK: Escrow vault
-    #[account(mut)]
-    pub escrow_vault: UncheckedAccount<'info>,
-
-    /// CHECK: Token program.
-    pub token_program: UncheckedAccount<'info>,
-
-    #[account(mut)]
-    /// CHECK: Token vault account of vault A
-    pub a_token_vault: UncheckedAccount<'info>,
-    #[account(mut)]
-    /// CHECK: Token vault account of vault B
-    pub b_token_vault: UncheckedAccount<'info>,
+pub fn sell_for_exact_sol(ctx: Context<Sell>, sol_out: u64, max_tokens_in: u64) -> Result<()> {
+    require!(
+        ctx.accounts.global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
 
-    /// CHECK: Vault account for token a. token a of the pool will be deposit / withdraw from this vault account.
-    #[account(mut)]
-    pub a_vault: UncheckedAccount<'info>,
-    /// CHECK: Vault account for token b. token b of the pool will be deposit / withdraw from this vault account.
-    #[account(mut)]
-    pub b_vault: UncheckedAccount<'info>,
-    /// CHECK: LP token account of vault A. Used to receive/burn the vault LP upon deposit/withdraw from the vault.
-    #[account(mut)]
-    pub a_vault_lp: UncheckedAccount<'info>,
-    /// CHECK: LP token account of vault B. Used to receive/burn the vault LP upon deposit/withdraw from the vault.
-    #[account(mut)]
-    pub b_vault_lp: UncheckedAccount<'info>,
-    /// CHECK: LP token mint of vault a
-    #[account(mut)]
-    pub a_vault_lp_mint: Account<'info, Mint>,
-    /// CHECK: LP token mint of vault b
-    #[account(mut)]
-    pub b_vault_lp_mint: Account<'info, Mint>,
+    let curve_model = ctx
+        .accounts
+        .bonding_curve
+        .model(ctx.accounts.custom_curve.as_deref())?;
+
+    //token amount is rounded up so the curve never pays out more than sol_out is worth
+    //the fee tier is picked from the SOL the tokens sell for before the fee, so size the sell with
+    //the tier of sol_out and settle on the tier it executes at
+    let global = &ctx.accounts.global;
+    let progress_basis_points = ctx.accounts.bonding_curve.progress_basis_points();
+    let tokens_for_sol_out = |protocol_fee_basis_points: u64| {
+        curve_model
+            .tokens_for_sol_out(sol_out as u128, global.total_fee_basis_points(protocol_fee_basis_points) as u128)
+            .ok_or(CurveLaunchpadError::InsufficientSOL)
+    };
 
-    #[account(mut, associated_token::mint = a_vault_lp_mint, associated_token::authority = Pubkey::from_str("6DVUvbq19v7EyBANDoqmJ2zvVpjpnYeKKY4fGaMa1iuL").unwrap())]
-    /// CHECK: User token A account. Token will be transfer from this account if it is add liquidity operation. Else, token will be transfer into this account.
-    pub user_a_token: Account<'info, TokenAccount>,
-    #[account(mut, associated_token::mint = b_vault_lp_mint, associated_token::authority = Pubkey::from_str("6DVUvbq19v7EyBANDoqmJ2zvVpjpnYeKKY4fGaMa1iuL").unwrap())]
-    /// CHECK: User token B account. Token will be transfer from this account if it is add liquidity operation. Else, token will be transfer into this account.
-    pub user_b_token: Account<'info, TokenAccount>,
+    let estimated_sol_amount = curve_model
+        .sell_quote(tokens_for_sol_out(global.protocol_fee_basis_points(progress_basis_points, sol_out))?)
+        .ok_or(CurveLaunchpadError::InsufficientSOL)?;
+    let protocol_fee_basis_points = global.protocol_fee_basis_points(progress_basis_points, estimated_sol_amount as u64);
+    let token_amount = tokens_for_sol_out(protocol_fee_basis_points)?;
 
-    /// CHECK: Vault program. the pool will deposit/withdraw liquidity from the vault.
-    pub vault_program: UncheckedAccount<'info>,
+    require!(
+        token_amount <= max_tokens_in as u128,
+        CurveLaunchpadError::MaxTokenInputExceeded,
+    );
 
-    #[account(
-        seeds = [BondingCurve::SEED_PREFIX, a_vault_lp_mint.to_account_info().key.as_ref()],
-        bump,
-    )]
-    pub bonding_curve: Box<Account<'info, BondingCurve>>,
+    //sol_out is the min output, sell rechecks it after the fee at the quoted tier
+    sell_tokens(ctx, token_amount as u64, sol_out, Some(protocol_fee_basis_points))
 }
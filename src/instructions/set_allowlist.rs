@@ -0,0 +1,72 @@
+use crate::{
+    state::{Allowlist, BondingCurve},
+    CurveLaunchpadError, SetAllowlistEvent, MAX_PRESALE_DURATION,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAllowlist<'info> {
+    creator: Signer<'info>,
+
+    mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [BondingCurve::SEED_PREFIX, mint.to_account_info().key.as_ref()],
+        bump,
+        has_one = creator,
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn set_allowlist(
+    ctx: Context<SetAllowlist>,
+    merkle_root: [u8; 32],
+    start_timestamp: i64,
+    public_timestamp: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+
+    //holders can't sell during a presale, so one can't be put on a curve that is already trading
+    require!(
+        !bonding_curve.is_public_trading_open(timestamp, clock.slot)
+            && bonding_curve.real_token_reserves == bonding_curve.initial_real_token_reserves,
+        CurveLaunchpadError::TradingStarted
+    );
+
+    //a running presale can't be changed
+    require!(
+        !bonding_curve.allowlist.is_enabled() || timestamp < bonding_curve.allowlist.start_timestamp,
+        CurveLaunchpadError::AllowlistStarted
+    );
+
+    //the new phase must start in the future and end after it starts, within the max presale duration
+    require!(
+        timestamp < start_timestamp
+            && start_timestamp <= public_timestamp
+            && public_timestamp - start_timestamp <= MAX_PRESALE_DURATION,
+        CurveLaunchpadError::InvalidAllowlist
+    );
+
+    bonding_curve.allowlist = Allowlist {
+        merkle_root,
+        start_timestamp,
+        public_timestamp,
+    };
+
+    emit_cpi!(SetAllowlistEvent {
+        mint: *ctx.accounts.mint.to_account_info().key,
+        bonding_curve: *ctx.accounts.bonding_curve.to_account_info().key,
+        merkle_root,
+        start_timestamp,
+        public_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{state::Global, CurveLaunchpadError, SetFeeScheduleEvent};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    user: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn set_fee_schedule(
+    ctx: Context<SetFeeSchedule>,
+    protocol_fee_basis_points: u64,
+    creator_fee_basis_points: u64,
+    referrer_fee_basis_points: u64,
+    creator_lp_fee_basis_points: u64,
+) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+
+    //confirm program is initialized
+    require!(
+        global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    //confirm user is the authority
+    require!(
+        global.authority == *ctx.accounts.user.to_account_info().key,
+        CurveLaunchpadError::InvalidAuthority
+    );
+
+    //the creator LP share is taken out of the claimed LP fees
+    require!(
+        creator_lp_fee_basis_points <= 10000,
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
+
+    global.fee_basis_points = protocol_fee_basis_points;
+    global.creator_fee_basis_points = creator_fee_basis_points;
+    global.referrer_fee_basis_points = referrer_fee_basis_points;
+    global.creator_lp_fee_basis_points = creator_lp_fee_basis_points;
+
+    //the combined fee can't exceed the trade, whichever fee tier applies
+    require!(
+        global.total_fee_basis_points(global.max_protocol_fee_basis_points()) < 10000,
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
+
+    emit_cpi!(SetFeeScheduleEvent {
+        protocol_fee_basis_points,
+        creator_fee_basis_points,
+        referrer_fee_basis_points,
+        creator_lp_fee_basis_points,
+    });
+
+    Ok(())
+}
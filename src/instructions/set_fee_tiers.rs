@@ -0,0 +1,77 @@
+use crate::{
+    state::{FeeTier, Global, MAX_FEE_TIERS},
+    CurveLaunchpadError, SetFeeTiersEvent,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeTiers<'info> {
+    #[account(
+        mut,
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    user: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+//thresholds must be strictly increasing so each trade matches at most one tier per table
+fn tiers_are_sorted(tiers: &[FeeTier]) -> bool {
+    tiers.windows(2).all(|pair| pair[0].threshold < pair[1].threshold)
+}
+
+pub fn set_fee_tiers(
+    ctx: Context<SetFeeTiers>,
+    progress_fee_tiers: Vec<FeeTier>,
+    size_fee_tiers: Vec<FeeTier>,
+) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+
+    //confirm program is initialized
+    require!(
+        global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    //confirm user is the authority
+    require!(
+        global.authority == *ctx.accounts.user.to_account_info().key,
+        CurveLaunchpadError::InvalidAuthority
+    );
+
+    require!(
+        progress_fee_tiers.len() <= MAX_FEE_TIERS && size_fee_tiers.len() <= MAX_FEE_TIERS,
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
+
+    require!(
+        tiers_are_sorted(&progress_fee_tiers) && tiers_are_sorted(&size_fee_tiers),
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
+
+    //progress is measured in basis points of the curve
+    require!(
+        progress_fee_tiers.iter().all(|tier| tier.threshold <= 10000),
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
+
+    global.progress_fee_tiers = progress_fee_tiers.clone();
+    global.size_fee_tiers = size_fee_tiers.clone();
+
+    //the combined fee can't exceed the trade, whichever fee tier applies
+    require!(
+        global.total_fee_basis_points(global.max_protocol_fee_basis_points()) < 10000,
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
+
+    emit_cpi!(SetFeeTiersEvent {
+        progress_fee_tiers,
+        size_fee_tiers,
+    });
+
+    Ok(())
+}
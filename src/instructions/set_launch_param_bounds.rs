@@ -0,0 +1,56 @@
+use crate::{
+    state::{Global, LaunchParams},
+    CurveLaunchpadError, SetLaunchParamBoundsEvent,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLaunchParamBounds<'info> {
+    #[account(
+        mut,
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    user: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn set_launch_param_bounds(
+    ctx: Context<SetLaunchParamBounds>,
+    min_launch_params: LaunchParams,
+    max_launch_params: LaunchParams,
+) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+
+    //confirm program is initialized
+    require!(
+        global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    //confirm user is the authority
+    require!(
+        global.authority == *ctx.accounts.user.to_account_info().key,
+        CurveLaunchpadError::InvalidAuthority
+    );
+
+    //every bound must be a non empty range
+    require!(
+        min_launch_params.is_within(&min_launch_params, &max_launch_params),
+        CurveLaunchpadError::InvalidLaunchParams
+    );
+
+    global.min_launch_params = min_launch_params;
+    global.max_launch_params = max_launch_params;
+
+    emit_cpi!(SetLaunchParamBoundsEvent {
+        min_launch_params,
+        max_launch_params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::{state::Global, CurveLaunchpadError, SetLockProgramEvent};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLockProgram<'info> {
+    #[account(
+        mut,
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    user: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+//the vault program a creator dev buy can be locked into, the default key disables locking
+pub fn set_lock_program(ctx: Context<SetLockProgram>, lock_program: Pubkey) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+
+    //confirm program is initialized
+    require!(
+        global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    //confirm user is the authority
+    require!(
+        global.authority == *ctx.accounts.user.to_account_info().key,
+        CurveLaunchpadError::InvalidAuthority
+    );
+
+    global.lock_program = lock_program;
+
+    emit_cpi!(SetLockProgramEvent { lock_program });
+
+    Ok(())
+}
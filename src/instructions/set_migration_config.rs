@@ -0,0 +1,41 @@
+use crate::{state::Global, CurveLaunchpadError, SetMigrationConfigEvent};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMigrationConfig<'info> {
+    #[account(
+        mut,
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    user: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+//the dynamic_amm pool config migrate creates pools with, the caller can't pick its own fee config.
+//migrate only accepts a config whose pool creator authority is the pool-authority PDA
+pub fn set_migration_config(ctx: Context<SetMigrationConfig>, dynamic_amm_config: Pubkey) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+
+    //confirm program is initialized
+    require!(
+        global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    //confirm user is the authority
+    require!(
+        global.authority == *ctx.accounts.user.to_account_info().key,
+        CurveLaunchpadError::InvalidAuthority
+    );
+
+    global.dynamic_amm_config = dynamic_amm_config;
+
+    emit_cpi!(SetMigrationConfigEvent { dynamic_amm_config });
+
+    Ok(())
+}
@@ -1,6 +1,18 @@
-use crate::{state::Global, CurveLaunchpadError, SetParamsEvent};
+use crate::{state::Global, CurveLaunchpadError, SetParamsEvent, MAX_MIGRATION_FEE};
 use anchor_lang::prelude::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SetParamsArgs {
+    pub fee_recipient: Pubkey,
+    pub withdraw_authority: Pubkey,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub initial_token_supply: u64,
+    pub fee_basis_points: u64,
+    pub migration_fee: u64,
+}
+
 #[event_cpi]
 #[derive(Accounts)]
 pub struct SetParams<'info> {
@@ -16,16 +28,18 @@ pub struct SetParams<'info> {
     system_program: Program<'info, System>,
 }
 
-pub fn set_params(
-    ctx: Context<SetParams>,
-    fee_recipient: Pubkey,
-    withdraw_authority: Pubkey,
-    initial_virtual_token_reserves: u64,
-    initial_virtual_sol_reserves: u64,
-    initial_real_token_reserves: u64,
-    initial_token_supply: u64,
-    fee_basis_points: u64,
-) -> Result<()> {
+pub fn set_params(ctx: Context<SetParams>, args: SetParamsArgs) -> Result<()> {
+    let SetParamsArgs {
+        fee_recipient,
+        withdraw_authority,
+        initial_virtual_token_reserves,
+        initial_virtual_sol_reserves,
+        initial_real_token_reserves,
+        initial_token_supply,
+        fee_basis_points,
+        migration_fee,
+    } = args;
+
     let global = &mut ctx.accounts.global;
 
     //confirm program is initialized
@@ -39,6 +53,11 @@ pub fn set_params(
         global.authority == *ctx.accounts.user.to_account_info().key,
         CurveLaunchpadError::InvalidAuthority
     );
+
+    require!(
+        migration_fee <= MAX_MIGRATION_FEE,
+        CurveLaunchpadError::InvalidMigrationFee
+    );
     
     global.fee_recipient = fee_recipient;
     global.initial_virtual_token_reserves = initial_virtual_token_reserves;
@@ -47,6 +66,13 @@ pub fn set_params(
     global.initial_token_supply = initial_token_supply;
     global.fee_basis_points = fee_basis_points;
     global.withdraw_authority = withdraw_authority;
+    global.migration_fee = migration_fee;
+
+    //the combined fee can't exceed the trade, whichever fee tier applies
+    require!(
+        global.total_fee_basis_points(global.max_protocol_fee_basis_points()) < 10000,
+        CurveLaunchpadError::InvalidFeeSchedule
+    );
 
     emit_cpi!(SetParamsEvent {
         fee_recipient,
@@ -56,6 +82,7 @@ pub fn set_params(
         initial_real_token_reserves,
         initial_token_supply,
         fee_basis_points,
+        migration_fee,
     });
 
     Ok(())
@@ -0,0 +1,53 @@
+use crate::{state::Global, CurveLaunchpadError, SetWithdrawModeEvent, MAX_OPERATOR_FEE_BASIS_POINTS};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetWithdrawMode<'info> {
+    #[account(
+        mut,
+        seeds = [Global::SEED_PREFIX],
+        bump,
+    )]
+    global: Box<Account<'info, Global>>,
+
+    user: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+//only applies to curves created afterwards, existing curves keep the mode they were created with
+pub fn set_withdraw_mode(
+    ctx: Context<SetWithdrawMode>,
+    trustless_withdraw: bool,
+    operator_fee_basis_points: u64,
+) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+
+    //confirm program is initialized
+    require!(
+        global.initialized,
+        CurveLaunchpadError::NotInitialized
+    );
+
+    //confirm user is the authority
+    require!(
+        global.authority == *ctx.accounts.user.to_account_info().key,
+        CurveLaunchpadError::InvalidAuthority
+    );
+
+    require!(
+        operator_fee_basis_points <= MAX_OPERATOR_FEE_BASIS_POINTS,
+        CurveLaunchpadError::InvalidOperatorFee
+    );
+
+    global.trustless_withdraw = trustless_withdraw;
+    global.operator_fee_basis_points = operator_fee_basis_points;
+
+    emit_cpi!(SetWithdrawModeEvent {
+        trustless_withdraw,
+        operator_fee_basis_points,
+    });
+
+    Ok(())
+}
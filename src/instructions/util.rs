@@ -1,3 +1,6 @@
+use anchor_lang::solana_program::keccak::hashv;
+
+pub use crate::utils::merkle::verify_merkle_proof;
 
 pub fn calculate_fee(
     amount: u64,
@@ -6,6 +9,103 @@ pub fn calculate_fee(
     amount * fee_basis_points / 10000
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub protocol_fee: u64,
+    pub creator_fee: u64,
+    pub referrer_fee: u64,
+}
+
+impl FeeSplit {
+    pub fn total(&self) -> u64 {
+        self.protocol_fee + self.creator_fee + self.referrer_fee
+    }
+}
+
+//without a referrer the referrer share goes to the protocol
+pub fn split_fee(
+    amount: u64,
+    protocol_fee_basis_points: u64,
+    creator_fee_basis_points: u64,
+    referrer_fee_basis_points: u64,
+    has_referrer: bool,
+) -> FeeSplit {
+    let referrer_fee = calculate_fee(amount, referrer_fee_basis_points);
+    let (protocol_fee, referrer_fee) = if has_referrer {
+        (calculate_fee(amount, protocol_fee_basis_points), referrer_fee)
+    } else {
+        (calculate_fee(amount, protocol_fee_basis_points) + referrer_fee, 0)
+    };
+
+    FeeSplit {
+        protocol_fee,
+        creator_fee: calculate_fee(amount, creator_fee_basis_points),
+        referrer_fee,
+    }
+}
+
+//how far the curve has sold through its real tokens, in basis points
+pub fn curve_progress_basis_points(
+    initial_real_token_reserves: u64,
+    real_token_reserves: u64,
+) -> u64 {
+    if initial_real_token_reserves == 0 {
+        return 10000;
+    }
+
+    let sold = initial_real_token_reserves.saturating_sub(real_token_reserves) as u128;
+    (sold * 10000 / initial_real_token_reserves as u128) as u64
+}
+
+//tiers are (threshold, fee_basis_points) sorted by threshold
+//a progress tier applies while progress is below its threshold, a size tier once the trade reaches its threshold
+//the highest matching tier wins so a small trade can't dodge an early tier, the base applies when none match
+pub fn tiered_fee_basis_points(
+    progress_tiers: impl IntoIterator<Item = (u64, u64)>,
+    size_tiers: impl IntoIterator<Item = (u64, u64)>,
+    progress_basis_points: u64,
+    sol_amount: u64,
+    base_fee_basis_points: u64,
+) -> u64 {
+    let progress_fee = progress_tiers
+        .into_iter()
+        .find(|(max_progress, _)| progress_basis_points < *max_progress)
+        .map(|(_, fee_basis_points)| fee_basis_points);
+    let size_fee = size_tiers
+        .into_iter()
+        .take_while(|(min_sol_amount, _)| sol_amount >= *min_sol_amount)
+        .last()
+        .map(|(_, fee_basis_points)| fee_basis_points);
+
+    match (progress_fee, size_fee) {
+        (Some(progress_fee), Some(size_fee)) => progress_fee.max(size_fee),
+        (Some(fee), None) | (None, Some(fee)) => fee,
+        (None, None) => base_fee_basis_points,
+    }
+}
+
+//leaves are keccak(wallet || max_allocation as little endian)
+pub fn allowlist_leaf(wallet: &[u8; 32], max_allocation: u64) -> [u8; 32] {
+    hashv(&[wallet, &max_allocation.to_le_bytes()]).to_bytes()
+}
+
+//nothing before the cliff, then linear from the start until duration has elapsed
+pub fn vested_amount(
+    total_amount: u64,
+    elapsed: i64,
+    cliff_duration: i64,
+    duration: i64,
+) -> u64 {
+    if elapsed < cliff_duration || elapsed <= 0 {
+        return 0;
+    }
+    if elapsed >= duration {
+        return total_amount;
+    }
+
+    (total_amount as u128 * elapsed as u128 / duration as u128) as u64
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -21,4 +121,103 @@ mod tests {
         assert_eq!(calculate_fee(1000, 50), 5); //0.5% fee
         assert_eq!(calculate_fee(100, 0), 0); //0% fee
     }
+
+    #[test]
+    fn test_split_fee() {
+        let split = split_fee(10000, 50, 30, 20, true);
+        assert_eq!(split, FeeSplit { protocol_fee: 50, creator_fee: 30, referrer_fee: 20 });
+        assert_eq!(split.total(), 100);
+
+        //no referrer, the protocol keeps the referrer share
+        let split = split_fee(10000, 50, 30, 20, false);
+        assert_eq!(split, FeeSplit { protocol_fee: 70, creator_fee: 30, referrer_fee: 0 });
+
+        //each share rounds down on its own, never above the combined fee
+        let split = split_fee(199, 50, 30, 20, true);
+        assert_eq!(split.total(), 0);
+        assert!(split.total() <= calculate_fee(199, 100));
+    }
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let hash_pair = |a: [u8; 32], b: [u8; 32]| {
+            if a <= b {
+                hashv(&[&a, &b]).to_bytes()
+            } else {
+                hashv(&[&b, &a]).to_bytes()
+            }
+        };
+
+        let leaves: Vec<[u8; 32]> = (0..4u8)
+            .map(|i| allowlist_leaf(&[i; 32], 1_000 * (i as u64 + 1)))
+            .collect();
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[2], left], &root, leaves[3]));
+
+        //a wrong allocation, a wrong sibling or a missing level all fail
+        assert!(!verify_merkle_proof(&[leaves[1], right], &root, allowlist_leaf(&[0; 32], 2_000)));
+        assert!(!verify_merkle_proof(&[leaves[2], right], &root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1]], &root, leaves[0]));
+
+        //a single leaf tree is its own root
+        assert!(verify_merkle_proof(&[], &leaves[0], leaves[0]));
+    }
+
+    #[test]
+    fn test_vested_amount() {
+        //linear without cliff
+        assert_eq!(vested_amount(1000, -5, 0, 100), 0);
+        assert_eq!(vested_amount(1000, 0, 0, 100), 0);
+        assert_eq!(vested_amount(1000, 25, 0, 100), 250);
+        assert_eq!(vested_amount(1000, 100, 0, 100), 1000);
+        assert_eq!(vested_amount(1000, 1_000, 0, 100), 1000);
+
+        //linear with cliff, the cliff releases what accrued so far
+        assert_eq!(vested_amount(1000, 24, 25, 100), 0);
+        assert_eq!(vested_amount(1000, 25, 25, 100), 250);
+
+        //cliff at the end releases everything at once
+        assert_eq!(vested_amount(1000, 99, 100, 100), 0);
+        assert_eq!(vested_amount(1000, 100, 100, 100), 1000);
+
+        //no overflow on large amounts
+        assert_eq!(vested_amount(u64::MAX, 50, 0, 100), u64::MAX / 2);
+    }
+
+    #[test]
+    fn test_curve_progress_basis_points() {
+        assert_eq!(curve_progress_basis_points(1000, 1000), 0);
+        assert_eq!(curve_progress_basis_points(1000, 750), 2500);
+        assert_eq!(curve_progress_basis_points(1000, 0), 10000);
+        assert_eq!(curve_progress_basis_points(u64::MAX, 0), 10000);
+        assert_eq!(curve_progress_basis_points(0, 0), 10000);
+    }
+
+    #[test]
+    fn test_tiered_fee_basis_points() {
+        let progress_tiers = [(1000, 500), (2000, 200)];
+        let size_tiers = [(1_000, 100), (10_000, 300)];
+
+        //no tiers, the base fee applies
+        assert_eq!(tiered_fee_basis_points([], [], 0, 0, 50), 50);
+
+        //the first progress tier still open applies
+        assert_eq!(tiered_fee_basis_points(progress_tiers, [], 0, 0, 50), 500);
+        assert_eq!(tiered_fee_basis_points(progress_tiers, [], 999, 0, 50), 500);
+        assert_eq!(tiered_fee_basis_points(progress_tiers, [], 1000, 0, 50), 200);
+        assert_eq!(tiered_fee_basis_points(progress_tiers, [], 2000, 0, 50), 50);
+
+        //the largest size bucket reached applies
+        assert_eq!(tiered_fee_basis_points([], size_tiers, 0, 999, 50), 50);
+        assert_eq!(tiered_fee_basis_points([], size_tiers, 0, 1_000, 50), 100);
+        assert_eq!(tiered_fee_basis_points([], size_tiers, 0, 50_000, 50), 300);
+
+        //both match, the higher one wins
+        assert_eq!(tiered_fee_basis_points(progress_tiers, size_tiers, 1500, 50_000, 50), 300);
+        assert_eq!(tiered_fee_basis_points(progress_tiers, size_tiers, 500, 50_000, 50), 500);
+    }
 }
\ No newline at end of file
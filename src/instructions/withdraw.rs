@@ -25,7 +25,7 @@ pub struct Withdraw<'info> {
     #[account(
         init_if_needed,
         space = 8 + LastWithdraw::INIT_SPACE,
-        seeds = [LastWithdraw::SEED_PREFIX],
+        seeds = [LastWithdraw::SEED_PREFIX, mint.to_account_info().key.as_ref()],
         bump,
         payer = user,
     )]
@@ -71,6 +71,12 @@ pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
         CurveLaunchpadError::BondingCurveNotComplete,
     );
 
+    //trustless curves can only be emptied by migrating them
+    require!(
+        !ctx.accounts.bonding_curve.trustless_withdraw,
+        CurveLaunchpadError::WithdrawDisabled,
+    );
+
     require!(
         ctx.accounts.user.key() == ctx.accounts.global.withdraw_authority,
         CurveLaunchpadError::InvalidWithdrawAuthority,
@@ -115,6 +121,7 @@ pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
 
     //update last withdraw
     let last_withdraw = &mut ctx.accounts.last_withdraw;
+    last_withdraw.bonding_curve = ctx.accounts.bonding_curve.key();
     last_withdraw.last_withdraw_timestamp = Clock::get()?.unix_timestamp;
 
     Ok(())
@@ -1,8 +1,23 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use std::convert::TryInto;
+use state::CustomCurve;
+use utils::curve_calculations::{calculate_sol_in, calculate_sol_out, calculate_tokens_out};
 
-declare_id!("YourProgramID");
+use instructions::*;
+use state::{Breakpoint, FeeTier, LaunchParams};
+
+pub mod instructions;
+pub mod state;
+pub mod amm;
+#[path = "../utils/mod.rs"]
+pub mod utils;
+
+declare_id!("GVapdHoG4xjJZpvGPd8EUBaUJKR5Txpf6VHnVwBVCY69");
+
+declare_program!(dynamic_amm);
+declare_program!(raydium_cp_swap);
+declare_program!(token_lock);
 
 #[program]
 pub mod complete_solana_project {
@@ -19,7 +34,7 @@ pub mod complete_solana_project {
         let current_time = Clock::get()?.unix_timestamp;
         let unlock_time = current_time + lock_duration;
 
-        token::transfer(ctx.accounts.into_transfer_to_vault_context(), amount)?;
+        token::transfer(ctx.accounts.transfer_to_vault_context(), amount)?;
 
         ctx.accounts.vault.locked_until = unlock_time;
         ctx.accounts.vault.locked_amount = amount;
@@ -29,13 +44,13 @@ pub mod complete_solana_project {
 
     pub fn unlock_tokens(ctx: Context<UnlockTokens>) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
-        let vault = &mut ctx.accounts.vault;
+        let locked_amount = ctx.accounts.vault.locked_amount;
 
-        require!(current_time >= vault.locked_until, CustomError::TokensStillLocked);
+        require!(current_time >= ctx.accounts.vault.locked_until, CustomError::TokensStillLocked);
 
-        token::transfer(ctx.accounts.into_transfer_from_vault_context(), vault.locked_amount)?;
+        token::transfer(ctx.accounts.transfer_from_vault_context(), locked_amount)?;
 
-        vault.locked_amount = 0;
+        ctx.accounts.vault.locked_amount = 0;
         Ok(())
     }
 
@@ -45,6 +60,12 @@ pub mod complete_solana_project {
         curve_type: u8,
         custom_params: [u64; 3],
     ) -> Result<()> {
+        if curve_type == 5 {
+            // A custom curve needs breakpoints starting at zero supply, monotone in both supply and price
+            let custom_curve = ctx.accounts.custom_curve.as_ref().ok_or(CustomError::InvalidCurveBreakpoints)?;
+            require!(custom_curve.price_function().is_some(), CustomError::InvalidCurveBreakpoints);
+        }
+
         let curve = &mut ctx.accounts.curve;
         curve.creator = ctx.accounts.creator.key();
         curve.mint = ctx.accounts.mint.key();
@@ -53,13 +74,15 @@ pub mod complete_solana_project {
         curve.reserve_sol = 0;
         curve.curve_type = curve_type;
         curve.custom_params = custom_params;
-        curve.bump = *ctx.bumps.get("curve").unwrap();
+        curve.bump = ctx.bumps.curve;
         Ok(())
     }
 
     pub fn buy_tokens(ctx: Context<BuyTokens>, amount: u64) -> Result<()> {
         let curve = &mut ctx.accounts.curve;
-        let tokens_out = calculate_tokens_out(curve, amount)?;
+        let tokens_out = calculate_tokens_out(curve, ctx.accounts.custom_curve.as_deref(), amount)?;
+        //amount is the most the buyer spends, only the price of the tokens delivered is charged
+        let sol_in = calculate_sol_in(curve, ctx.accounts.custom_curve.as_deref(), tokens_out)?;
 
         let cpi_context = CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
@@ -68,7 +91,7 @@ pub mod complete_solana_project {
                 to: ctx.accounts.sol_vault.to_account_info(),
             },
         );
-        anchor_lang::system_program::transfer(cpi_context, amount)?;
+        anchor_lang::system_program::transfer(cpi_context, sol_in)?;
 
         let seeds = &[b"curve".as_ref(), &ctx.accounts.mint.key().to_bytes(), &[curve.bump]];
         let signer = &[&seeds[..]];
@@ -82,14 +105,14 @@ pub mod complete_solana_project {
         token::transfer(cpi_ctx, tokens_out)?;
 
         curve.reserve_token -= tokens_out;
-        curve.reserve_sol += amount;
+        curve.reserve_sol += sol_in;
 
         Ok(())
     }
 
     pub fn sell_tokens(ctx: Context<SellTokens>, amount: u64) -> Result<()> {
         let curve = &mut ctx.accounts.curve;
-        let sol_out = calculate_sol_out(curve, amount)?;
+        let sol_out = calculate_sol_out(curve, ctx.accounts.custom_curve.as_deref(), amount)?;
 
         let cpi_accounts = token::Transfer {
             from: ctx.accounts.seller_token_account.to_account_info(),
@@ -133,7 +156,7 @@ pub mod complete_solana_project {
         vesting.end_time = end_time;
         vesting.target_market_cap = target_market_cap;
         vesting.is_locked = true;
-        vesting.bump = *ctx.bumps.get("vesting").unwrap();
+        vesting.bump = ctx.bumps.vesting;
         Ok(())
     }
 
@@ -141,7 +164,7 @@ pub mod complete_solana_project {
         let vesting = &mut ctx.accounts.vesting;
         require!(amount == vesting.amount, CustomError::InvalidVestingAmount);
 
-        token::transfer(ctx.accounts.into_transfer_to_vesting_context(), amount)?;
+        token::transfer(ctx.accounts.transfer_to_vesting_context(), amount)?;
 
         Ok(())
     }
@@ -173,6 +196,151 @@ pub mod complete_solana_project {
         vesting.is_locked = false;
         Ok(())
     }
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        initialize::initialize(ctx)
+    }
+
+    pub fn create(ctx: Context<Create>, args: CreateArgs) -> Result<()> {
+        create::create(ctx, args)
+    }
+
+    pub fn create_custom_curve(ctx: Context<CreateCustomCurve>, breakpoints: Vec<Breakpoint>) -> Result<()> {
+        create_custom_curve::create_custom_curve(ctx, breakpoints)
+    }
+
+    pub fn buy(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()> {
+        buy::buy(ctx, token_amount, max_sol_cost)
+    }
+
+    pub fn buy_allowlisted(
+        ctx: Context<Buy>,
+        token_amount: u64,
+        max_sol_cost: u64,
+        max_allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        buy::buy_allowlisted(ctx, token_amount, max_sol_cost, max_allocation, proof)
+    }
+
+    pub fn buy_and_vest(ctx: Context<Buy>, token_amount: u64, max_sol_cost: u64) -> Result<()> {
+        buy::buy_and_vest(ctx, token_amount, max_sol_cost)
+    }
+
+    pub fn buy_exact_sol_in(ctx: Context<Buy>, sol_amount: u64, min_tokens_out: u64) -> Result<()> {
+        buy::buy_exact_sol_in(ctx, sol_amount, min_tokens_out)
+    }
+
+    pub fn buy_exact_sol_in_allowlisted(
+        ctx: Context<Buy>,
+        sol_amount: u64,
+        min_tokens_out: u64,
+        max_allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        buy::buy_exact_sol_in_allowlisted(ctx, sol_amount, min_tokens_out, max_allocation, proof)
+    }
+
+    pub fn sell(ctx: Context<Sell>, token_amount: u64, min_sol_output: u64) -> Result<()> {
+        sell::sell(ctx, token_amount, min_sol_output)
+    }
+
+    pub fn sell_for_exact_sol(ctx: Context<Sell>, sol_out: u64, max_tokens_in: u64) -> Result<()> {
+        sell::sell_for_exact_sol(ctx, sol_out, max_tokens_in)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        withdraw::withdraw(ctx)
+    }
+
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        migrate::migrate(ctx)
+    }
+
+    pub fn migrate_raydium(ctx: Context<MigrateRaydium>) -> Result<()> {
+        migrate_raydium::migrate_raydium(ctx)
+    }
+
+    pub fn set_params(ctx: Context<SetParams>, args: SetParamsArgs) -> Result<()> {
+        set_params::set_params(ctx, args)
+    }
+
+    pub fn set_launch_param_bounds(
+        ctx: Context<SetLaunchParamBounds>,
+        min_launch_params: LaunchParams,
+        max_launch_params: LaunchParams,
+    ) -> Result<()> {
+        set_launch_param_bounds::set_launch_param_bounds(ctx, min_launch_params, max_launch_params)
+    }
+
+    pub fn set_withdraw_mode(
+        ctx: Context<SetWithdrawMode>,
+        trustless_withdraw: bool,
+        operator_fee_basis_points: u64,
+    ) -> Result<()> {
+        set_withdraw_mode::set_withdraw_mode(ctx, trustless_withdraw, operator_fee_basis_points)
+    }
+
+    pub fn set_fee_schedule(
+        ctx: Context<SetFeeSchedule>,
+        protocol_fee_basis_points: u64,
+        creator_fee_basis_points: u64,
+        referrer_fee_basis_points: u64,
+        creator_lp_fee_basis_points: u64,
+    ) -> Result<()> {
+        set_fee_schedule::set_fee_schedule(
+            ctx,
+            protocol_fee_basis_points,
+            creator_fee_basis_points,
+            referrer_fee_basis_points,
+            creator_lp_fee_basis_points,
+        )
+    }
+
+    pub fn set_fee_tiers(
+        ctx: Context<SetFeeTiers>,
+        progress_fee_tiers: Vec<FeeTier>,
+        size_fee_tiers: Vec<FeeTier>,
+    ) -> Result<()> {
+        set_fee_tiers::set_fee_tiers(ctx, progress_fee_tiers, size_fee_tiers)
+    }
+
+    pub fn set_allowlist(
+        ctx: Context<SetAllowlist>,
+        merkle_root: [u8; 32],
+        start_timestamp: i64,
+        public_timestamp: i64,
+    ) -> Result<()> {
+        set_allowlist::set_allowlist(ctx, merkle_root, start_timestamp, public_timestamp)
+    }
+
+    pub fn set_lock_program(ctx: Context<SetLockProgram>, lock_program: Pubkey) -> Result<()> {
+        set_lock_program::set_lock_program(ctx, lock_program)
+    }
+
+    pub fn set_migration_config(ctx: Context<SetMigrationConfig>, dynamic_amm_config: Pubkey) -> Result<()> {
+        set_migration_config::set_migration_config(ctx, dynamic_amm_config)
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        register_referrer::register_referrer(ctx)
+    }
+
+    pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
+        claim_fees::claim_fees(ctx)
+    }
+
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        claim_creator_fees::claim_creator_fees(ctx)
+    }
+
+    pub fn claim_buy_vesting(ctx: Context<ClaimBuyVesting>) -> Result<()> {
+        claim_buy_vesting::claim_buy_vesting(ctx)
+    }
+
+    pub fn claim_lp_fees(ctx: Context<ClaimLpFees>) -> Result<()> {
+        claim_lp_fees::claim_lp_fees(ctx)
+    }
 }
 
 #[derive(Accounts)]
@@ -221,6 +389,8 @@ pub struct InitializeLaunch<'info> {
     )]
     pub curve: Account<'info, Curve>,
     pub mint: Account<'info, Mint>,
+    #[account(seeds = [CustomCurve::SEED_PREFIX, mint.key().as_ref()], bump)]
+    pub custom_curve: Option<Account<'info, CustomCurve>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
@@ -239,6 +409,8 @@ pub struct BuyTokens<'info> {
     #[account(mut)]
     pub buyer_token_account: Account<'info, TokenAccount>,
     pub mint: Account<'info, Mint>,
+    #[account(seeds = [CustomCurve::SEED_PREFIX, mint.key().as_ref()], bump)]
+    pub custom_curve: Option<Account<'info, CustomCurve>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
@@ -256,6 +428,8 @@ pub struct SellTokens<'info> {
     #[account(mut)]
     pub seller_token_account: Account<'info, TokenAccount>,
     pub mint: Account<'info, Mint>,
+    #[account(seeds = [CustomCurve::SEED_PREFIX, mint.key().as_ref()], bump)]
+    pub custom_curve: Option<Account<'info, CustomCurve>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
@@ -354,10 +528,12 @@ pub enum CustomError {
     VestingPeriodNotEnded,
     #[msg("Market cap target not reached")]
     MarketCapNotReached,
+    #[msg("Custom curve breakpoints are missing or not monotone")]
+    InvalidCurveBreakpoints,
 }
 
 impl<'info> LockTokens<'info> {
-    fn into_transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+    fn transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
             from: self.user_token_account.to_account_info().clone(),
             to: self.vault_token_account.to_account_info().clone(),
@@ -366,3 +542,25 @@ impl<'info> LockTokens<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 }
+
+impl<'info> UnlockTokens<'info> {
+    fn transfer_from_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.user_token_account.to_account_info().clone(),
+            authority: self.authority.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
+
+impl<'info> LockTokensForVesting<'info> {
+    fn transfer_to_vesting_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.owner_token_account.to_account_info().clone(),
+            to: self.vesting_token_account.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -1,6 +1,83 @@
 use anchor_lang::prelude::*;
 use std::fmt;
 
+use crate::amm::{self, BondingCurveModel, CurveReserves, SupplyCurve};
+use crate::state::{CustomCurve, VestingSchedule};
+use crate::{curve_progress_basis_points, CurveLaunchpadError};
+
+pub const CURVE_PARAMS_LEN: usize = 4;
+
+// curve_params layout per kind, prices are amm::PRICE_SCALE lamports per token base unit
+//   ConstantProduct: unused
+//   Exponential: [base_price, scale]
+//   Logarithmic: [base_price, slope, scale]
+//   Sigmoid: [base_price, height, midpoint, width]
+//   Bell: [base_price, peak, center, width]
+//   PiecewiseLinear: unused, the breakpoints live in the mint's CustomCurve account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum CurveKind {
+    ConstantProduct,
+    Exponential,
+    Logarithmic,
+    Sigmoid,
+    Bell,
+    PiecewiseLinear,
+}
+
+//the DEX a completed curve migrates into
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum MigrationTarget {
+    DynamicAmm,
+    RaydiumCpSwap,
+}
+
+//optional launch protection, a zero field disables that check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct LaunchProtection {
+    //trading opens once both the timestamp and the slot are reached
+    pub start_timestamp: i64,
+    pub start_slot: u64,
+    //seconds after start_timestamp during which each wallet is capped at max_wallet_buy tokens
+    pub protected_duration: i64,
+    pub max_wallet_buy: u64,
+    pub max_tokens_per_transaction: u64,
+}
+
+impl LaunchProtection {
+    pub fn is_trading_open(&self, timestamp: i64, slot: u64) -> bool {
+        timestamp >= self.start_timestamp && slot >= self.start_slot
+    }
+
+    pub fn is_wallet_capped(&self, timestamp: i64) -> bool {
+        self.max_wallet_buy > 0 && timestamp < self.start_timestamp.saturating_add(self.protected_duration)
+    }
+}
+
+//optional presale, an all-zero root disables it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct Allowlist {
+    //root over (wallet, max_allocation) leaves
+    pub merkle_root: [u8; 32],
+    //allowlisted wallets buy from start_timestamp, everyone from public_timestamp
+    pub start_timestamp: i64,
+    pub public_timestamp: i64,
+}
+
+impl Allowlist {
+    pub fn is_enabled(&self) -> bool {
+        self.merkle_root != [0; 32]
+    }
+
+    pub fn is_presale(&self, timestamp: i64) -> bool {
+        self.is_enabled() && self.start_timestamp <= timestamp && timestamp < self.public_timestamp
+    }
+
+    //without an allowlist trading is public as soon as the launch starts
+    pub fn is_public(&self, timestamp: i64) -> bool {
+        !self.is_enabled() || timestamp >= self.public_timestamp
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct BondingCurve {
@@ -10,23 +87,92 @@ pub struct BondingCurve {
     pub real_token_reserves: u64,
     pub token_total_supply: u64,
     pub complete: bool,
+    pub curve_kind: CurveKind,
+    pub curve_params: [u64; CURVE_PARAMS_LEN],
+    pub initial_real_token_reserves: u64,
+    pub initial_virtual_token_reserves: u64,
+    pub migrated: bool,
+    pub pool: Pubkey,
+    pub migration_target: MigrationTarget,
+    //snapshotted from Global at create so a launch keeps the terms it started with
+    pub trustless_withdraw: bool,
+    pub operator_fee_basis_points: u64,
+    pub migration_fee: u64,
+    pub creator: Pubkey,
+    //the creator takes the global creator LP share of fees claimed from the migrated pool
+    pub creator_lp_fees: bool,
+    pub launch_protection: LaunchProtection,
+    pub allowlist: Allowlist,
+    //schedule applied to buy_and_vest purchases, none disables it
+    pub vesting_schedule: Option<VestingSchedule>,
 }
 
 impl BondingCurve {
     pub const SEED_PREFIX: &'static [u8; 13] = b"bonding-curve";
+
+    pub fn model(&self, custom_curve: Option<&CustomCurve>) -> Result<Box<dyn BondingCurveModel>> {
+        let params = self.curve_params.map(|param| param as u128);
+        let supply = self.initial_real_token_reserves.saturating_sub(self.real_token_reserves) as u128;
+        let real_sol_reserves = self.real_sol_reserves as u128;
+        let real_token_reserves = self.real_token_reserves as u128;
+
+        let model: Option<Box<dyn BondingCurveModel>> = match self.curve_kind {
+            CurveKind::ConstantProduct => Some(Box::new(amm::AMM::new(
+                self.virtual_sol_reserves as u128,
+                self.virtual_token_reserves as u128,
+                real_sol_reserves,
+                real_token_reserves,
+                self.initial_virtual_token_reserves as u128,
+            ))),
+            CurveKind::Exponential => amm::Exponential::new(params[0], params[1])
+                .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+            CurveKind::Logarithmic => amm::Logarithmic::new(params[0], params[1], params[2])
+                .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+            CurveKind::Sigmoid => amm::Sigmoid::new(params[0], params[1], params[2], params[3])
+                .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+            CurveKind::Bell => amm::Bell::new(params[0], params[1], params[2], params[3])
+                .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+            CurveKind::PiecewiseLinear => custom_curve
+                .and_then(|custom_curve| custom_curve.price_function())
+                .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+        };
+
+        model.ok_or(error!(CurveLaunchpadError::InvalidCurveParams))
+    }
+
+    pub fn set_reserves(&mut self, reserves: CurveReserves) -> Result<()> {
+        let to_u64 = |reserve: u128| u64::try_from(reserve).map_err(|_| CurveLaunchpadError::CalculationError);
+
+        self.virtual_sol_reserves = to_u64(reserves.virtual_sol_reserves)?;
+        self.virtual_token_reserves = to_u64(reserves.virtual_token_reserves)?;
+        self.real_sol_reserves = to_u64(reserves.real_sol_reserves)?;
+        self.real_token_reserves = to_u64(reserves.real_token_reserves)?;
+
+        Ok(())
+    }
+
+    //public trading needs the launch started and any presale over
+    pub fn is_public_trading_open(&self, timestamp: i64, slot: u64) -> bool {
+        self.launch_protection.is_trading_open(timestamp, slot) && self.allowlist.is_public(timestamp)
+    }
+
+    pub fn progress_basis_points(&self) -> u64 {
+        curve_progress_basis_points(self.initial_real_token_reserves, self.real_token_reserves)
+    }
 }
 
 impl fmt::Display for BondingCurve {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "virtual_sol_reserves: {}, virtual_token_reserves: {}, real_sol_reserves: {}, real_token_reserves: {}, token_total_supply: {}, complete: {}",
+            "virtual_sol_reserves: {}, virtual_token_reserves: {}, real_sol_reserves: {}, real_token_reserves: {}, token_total_supply: {}, complete: {}, curve_kind: {:?}",
             self.virtual_sol_reserves,
             self.virtual_token_reserves,
             self.real_sol_reserves,
             self.real_token_reserves,
             self.token_total_supply,
-            self.complete
+            self.complete,
+            self.curve_kind
         )
     }
 }
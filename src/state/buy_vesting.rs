@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+
+use crate::{vested_amount, CurveLaunchpadError};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum VestingKind {
+    //released linearly over duration, nothing before cliff_duration
+    Linear,
+    //released all at once when duration has elapsed
+    Cliff,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct VestingSchedule {
+    pub kind: VestingKind,
+    pub cliff_duration: i64,
+    pub duration: i64,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        self.duration > 0 && 0 <= self.cliff_duration && self.cliff_duration <= self.duration
+    }
+
+    pub fn vested_amount(&self, total_amount: u64, elapsed: i64) -> u64 {
+        let cliff_duration = match self.kind {
+            VestingKind::Linear => self.cliff_duration,
+            VestingKind::Cliff => self.duration,
+        };
+
+        vested_amount(total_amount, elapsed, cliff_duration, self.duration)
+    }
+}
+
+//tokens bought through buy_and_vest, one per user and launch
+#[account]
+#[derive(InitSpace)]
+pub struct BuyVesting {
+    pub bonding_curve: Pubkey,
+    pub user: Pubkey,
+    pub schedule: VestingSchedule,
+    pub start_timestamp: i64,
+    //tokens on the schedule running from start_timestamp
+    pub total_amount: u64,
+    //tokens that vested on earlier schedules before a later buy restarted it
+    pub unlocked_amount: u64,
+    pub released_amount: u64,
+}
+
+impl BuyVesting {
+    pub const SEED_PREFIX: &'static [u8; 11] = b"buy-vesting";
+    pub const TOKEN_SEED_PREFIX: &'static [u8; 18] = b"buy-vesting-tokens";
+
+    fn vested_amount(&self, timestamp: i64) -> u64 {
+        self.schedule
+            .vested_amount(self.total_amount, timestamp.saturating_sub(self.start_timestamp))
+    }
+
+    pub fn releasable_amount(&self, timestamp: i64) -> u64 {
+        self.unlocked_amount
+            .saturating_add(self.vested_amount(timestamp))
+            .saturating_sub(self.released_amount)
+    }
+
+    //tranches don't keep their own schedules: a later buy moves what already vested to unlocked_amount
+    //and restarts the full schedule, cliff included, for itself and everything still unvested,
+    //so topping up can't unlock new tokens early but does push back the earlier buys' remainder
+    pub fn add(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        let vested_amount = self.vested_amount(timestamp);
+        self.unlocked_amount = self
+            .unlocked_amount
+            .checked_add(vested_amount)
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+        self.total_amount = self
+            .total_amount
+            .checked_sub(vested_amount)
+            .and_then(|unvested_amount| unvested_amount.checked_add(amount))
+            .ok_or(CurveLaunchpadError::CalculationError)?;
+        self.start_timestamp = timestamp;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn buy_vesting(kind: VestingKind, cliff_duration: i64, duration: i64) -> BuyVesting {
+        BuyVesting {
+            bonding_curve: Pubkey::default(),
+            user: Pubkey::default(),
+            schedule: VestingSchedule { kind, cliff_duration, duration },
+            start_timestamp: 0,
+            total_amount: 0,
+            unlocked_amount: 0,
+            released_amount: 0,
+        }
+    }
+
+    #[test]
+    fn test_top_up_after_full_vesting() {
+        let mut vesting = buy_vesting(VestingKind::Linear, 0, 100 * DAY);
+        vesting.add(1000, 0).unwrap();
+        vesting.add(1, 100 * DAY).unwrap();
+
+        //the first buy is fully vested, the top up starts its own 100 days
+        assert_eq!(vesting.releasable_amount(100 * DAY), 1000);
+        assert_eq!(vesting.releasable_amount(199 * DAY), 1000);
+        assert_eq!(vesting.releasable_amount(200 * DAY), 1001);
+    }
+
+    #[test]
+    fn test_top_up_restarts_unvested_remainder() {
+        let mut vesting = buy_vesting(VestingKind::Linear, 0, 100 * DAY);
+        vesting.add(1000, 0).unwrap();
+        vesting.released_amount = vesting.releasable_amount(50 * DAY);
+        assert_eq!(vesting.released_amount, 500);
+
+        //the unvested 500 and the new 500 vest together over a fresh 100 days
+        vesting.add(500, 50 * DAY).unwrap();
+        assert_eq!(vesting.releasable_amount(50 * DAY), 0);
+        assert_eq!(vesting.releasable_amount(100 * DAY), 500);
+        assert_eq!(vesting.releasable_amount(150 * DAY), 1000);
+
+        //a cliff schedule holds the whole remainder until the restarted cliff
+        let mut vesting = buy_vesting(VestingKind::Cliff, 0, 100 * DAY);
+        vesting.add(1000, 0).unwrap();
+        vesting.add(1000, 50 * DAY).unwrap();
+        assert_eq!(vesting.releasable_amount(100 * DAY), 0);
+        assert_eq!(vesting.releasable_amount(150 * DAY), 2000);
+    }
+}
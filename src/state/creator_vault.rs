@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+//one vault per creator, every curve they launched pays its creator fee share into it
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorVault {
+    pub creator: Pubkey,
+    pub total_accrued: u64,
+    pub total_claimed: u64,
+}
+
+impl CreatorVault {
+    pub const SEED_PREFIX: &'static [u8; 13] = b"creator-vault";
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::amm::PiecewiseLinear;
+
+pub const MAX_BREAKPOINTS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Breakpoint {
+    pub supply: u64,
+    pub price: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CustomCurve {
+    pub mint: Pubkey,
+    #[max_len(MAX_BREAKPOINTS)]
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+impl CustomCurve {
+    pub const SEED_PREFIX: &'static [u8; 12] = b"custom-curve";
+
+    pub fn price_function(&self) -> Option<PiecewiseLinear> {
+        PiecewiseLinear::new(
+            self.breakpoints
+                .iter()
+                .map(|breakpoint| (breakpoint.supply as u128, breakpoint.price as u128))
+                .collect(),
+        )
+    }
+}
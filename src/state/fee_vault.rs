@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+//holds the referrer share of trade fees until the owner claims them
+#[account]
+#[derive(InitSpace)]
+pub struct FeeVault {
+    pub owner: Pubkey,
+    pub total_accrued: u64,
+    pub total_claimed: u64,
+}
+
+impl FeeVault {
+    pub const SEED_PREFIX: &'static [u8; 9] = b"fee-vault";
+}
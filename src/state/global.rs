@@ -1,6 +1,50 @@
 use anchor_lang::prelude::*;
 
+use crate::tiered_fee_basis_points;
 
+//the share of the supply a launch has to keep out of the curve for the migrated pool
+pub const MIN_POOL_ALLOCATION_BASIS_POINTS: u64 = 1000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct LaunchParams {
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub initial_token_supply: u64,
+}
+
+impl LaunchParams {
+    pub fn is_within(&self, min: &LaunchParams, max: &LaunchParams) -> bool {
+        let within = |value: u64, min: u64, max: u64| min <= value && value <= max;
+
+        within(self.initial_virtual_token_reserves, min.initial_virtual_token_reserves, max.initial_virtual_token_reserves)
+            && within(self.initial_virtual_sol_reserves, min.initial_virtual_sol_reserves, max.initial_virtual_sol_reserves)
+            && within(self.initial_real_token_reserves, min.initial_real_token_reserves, max.initial_real_token_reserves)
+            && within(self.initial_token_supply, min.initial_token_supply, max.initial_token_supply)
+    }
+
+    //the curve can only sell what was minted, must never run out of virtual tokens and has to keep enough
+    //of the supply back to seed the pool it migrates to
+    pub fn is_consistent(&self) -> bool {
+        let pool_allocation = self.initial_token_supply.saturating_sub(self.initial_real_token_reserves) as u128;
+        let min_pool_allocation = self.initial_token_supply as u128 * MIN_POOL_ALLOCATION_BASIS_POINTS as u128 / 10_000;
+
+        self.initial_virtual_sol_reserves > 0
+            && self.initial_real_token_reserves > 0
+            && self.initial_real_token_reserves < self.initial_token_supply
+            && pool_allocation >= min_pool_allocation
+            && self.initial_real_token_reserves < self.initial_virtual_token_reserves
+    }
+}
+
+//threshold is the curve progress in basis points for progress tiers, the trade size in lamports for size tiers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct FeeTier {
+    pub threshold: u64,
+    pub fee_basis_points: u64,
+}
+
+pub const MAX_FEE_TIERS: usize = 8;
 
 #[account]
 #[derive(InitSpace)]
@@ -15,8 +59,61 @@ pub struct Global {
     pub initial_token_supply: u64,
     pub fee_basis_points: u64,
     pub withdraw_authority: Pubkey,
+    pub min_launch_params: LaunchParams,
+    pub max_launch_params: LaunchParams,
+    pub migration_fee: u64,
+    pub trustless_withdraw: bool,
+    pub operator_fee_basis_points: u64,
+    pub creator_fee_basis_points: u64,
+    pub referrer_fee_basis_points: u64,
+    pub creator_lp_fee_basis_points: u64,
+    //protocol share tiers, fee_basis_points applies when no tier matches
+    #[max_len(MAX_FEE_TIERS)]
+    pub progress_fee_tiers: Vec<FeeTier>,
+    #[max_len(MAX_FEE_TIERS)]
+    pub size_fee_tiers: Vec<FeeTier>,
+    pub lock_program: Pubkey,
+    //the only dynamic_amm config migrate accepts
+    pub dynamic_amm_config: Pubkey,
 }
 
 impl Global {
    pub const SEED_PREFIX: &'static [u8; 6] = b"global";
+
+   //the protocol share for a trade of sol_amount at the given curve progress
+   pub fn protocol_fee_basis_points(&self, progress_basis_points: u64, sol_amount: u64) -> u64 {
+       tiered_fee_basis_points(
+           self.progress_fee_tiers.iter().map(|tier| (tier.threshold, tier.fee_basis_points)),
+           self.size_fee_tiers.iter().map(|tier| (tier.threshold, tier.fee_basis_points)),
+           progress_basis_points,
+           sol_amount,
+           self.fee_basis_points,
+       )
+   }
+
+   //a trade pays the protocol, creator and referrer shares, saturating so an oversized schedule fails
+   //the < 10000 check in the fee setters instead of overflowing
+   pub fn total_fee_basis_points(&self, protocol_fee_basis_points: u64) -> u64 {
+       protocol_fee_basis_points
+           .saturating_add(self.creator_fee_basis_points)
+           .saturating_add(self.referrer_fee_basis_points)
+   }
+
+   //the largest protocol share any trade can be charged
+   pub fn max_protocol_fee_basis_points(&self) -> u64 {
+       self.progress_fee_tiers
+           .iter()
+           .chain(self.size_fee_tiers.iter())
+           .map(|tier| tier.fee_basis_points)
+           .fold(self.fee_basis_points, u64::max)
+   }
+
+   pub fn default_launch_params(&self) -> LaunchParams {
+       LaunchParams {
+           initial_virtual_token_reserves: self.initial_virtual_token_reserves,
+           initial_virtual_sol_reserves: self.initial_virtual_sol_reserves,
+           initial_real_token_reserves: self.initial_real_token_reserves,
+           initial_token_supply: self.initial_token_supply,
+       }
+   }
 }
\ No newline at end of file
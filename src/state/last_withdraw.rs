@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 #[account]
 #[derive(InitSpace)]
 pub struct LastWithdraw {
+    pub bonding_curve: Pubkey,
     pub last_withdraw_timestamp: i64,
 }
 
@@ -1,7 +1,17 @@
 pub mod global;
 pub mod bonding_curve;
 pub mod last_withdraw;
+pub mod custom_curve;
+pub mod fee_vault;
+pub mod creator_vault;
+pub mod wallet_purchase;
+pub mod buy_vesting;
 
 pub use global::*;
 pub use bonding_curve::*;
-pub use last_withdraw::*;
\ No newline at end of file
+pub use last_withdraw::*;
+pub use custom_curve::*;
+pub use fee_vault::*;
+pub use creator_vault::*;
+pub use wallet_purchase::*;
+pub use buy_vesting::*;
\ No newline at end of file
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+//tokens a wallet bought from one curve, checked against the launch protection wallet cap and the presale allocation
+#[account]
+#[derive(InitSpace)]
+pub struct WalletPurchase {
+    pub bonding_curve: Pubkey,
+    pub user: Pubkey,
+    pub tokens_bought: u64,
+}
+
+impl WalletPurchase {
+    pub const SEED_PREFIX: &'static [u8; 15] = b"wallet-purchase";
+}
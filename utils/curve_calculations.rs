@@ -1,26 +1,41 @@
 use anchor_lang::prelude::*;
-use crate::state::Curve;
+use crate::amm::{Bell, BondingCurveModel, Exponential, Logarithmic, Sigmoid, SupplyCurve};
+use crate::state::CustomCurve;
+use crate::Curve;
 
-pub fn calculate_tokens_out(curve: &Curve, sol_amount: u64) -> Result<u64> {
+pub fn calculate_tokens_out(curve: &Curve, custom_curve: Option<&CustomCurve>, sol_amount: u64) -> Result<u64> {
     match curve.curve_type {
         0 => calculate_linear_tokens_out(curve, sol_amount),
-        1 => calculate_exponential_tokens_out(curve, sol_amount),
-        2 => calculate_logarithmic_tokens_out(curve, sol_amount),
-        3 => calculate_sigmoid_tokens_out(curve, sol_amount),
-        4 => calculate_bell_tokens_out(curve, sol_amount),
-        5 => calculate_custom_tokens_out(curve, sol_amount),
+        1 => calculate_integral_tokens_out(curve, custom_curve, sol_amount), // exponential
+        2 => calculate_integral_tokens_out(curve, custom_curve, sol_amount), // logarithmic
+        3 => calculate_integral_tokens_out(curve, custom_curve, sol_amount), // sigmoid
+        4 => calculate_integral_tokens_out(curve, custom_curve, sol_amount), // bell
+        5 => calculate_integral_tokens_out(curve, custom_curve, sol_amount), // custom
         _ => Err(ProgramError::InvalidInstructionData.into()),
     }
 }
 
-pub fn calculate_sol_out(curve: &Curve, token_amount: u64) -> Result<u64> {
+pub fn calculate_sol_out(curve: &Curve, custom_curve: Option<&CustomCurve>, token_amount: u64) -> Result<u64> {
     match curve.curve_type {
         0 => calculate_linear_sol_out(curve, token_amount),
-        1 => calculate_exponential_sol_out(curve, token_amount),
-        2 => calculate_logarithmic_sol_out(curve, token_amount),
-        3 => calculate_sigmoid_sol_out(curve, token_amount),
-        4 => calculate_bell_sol_out(curve, token_amount),
-        5 => calculate_custom_sol_out(curve, token_amount),
+        1 => calculate_integral_sol_out(curve, custom_curve, token_amount), // exponential
+        2 => calculate_integral_sol_out(curve, custom_curve, token_amount), // logarithmic
+        3 => calculate_integral_sol_out(curve, custom_curve, token_amount), // sigmoid
+        4 => calculate_integral_sol_out(curve, custom_curve, token_amount), // bell
+        5 => calculate_integral_sol_out(curve, custom_curve, token_amount), // custom
+        _ => Err(ProgramError::InvalidInstructionData.into()),
+    }
+}
+
+// what buying token_amount costs, the price actually charged for the tokens calculate_tokens_out delivers
+pub fn calculate_sol_in(curve: &Curve, custom_curve: Option<&CustomCurve>, token_amount: u64) -> Result<u64> {
+    match curve.curve_type {
+        0 => calculate_linear_sol_out(curve, token_amount),
+        1 => calculate_integral_sol_in(curve, custom_curve, token_amount), // exponential
+        2 => calculate_integral_sol_in(curve, custom_curve, token_amount), // logarithmic
+        3 => calculate_integral_sol_in(curve, custom_curve, token_amount), // sigmoid
+        4 => calculate_integral_sol_in(curve, custom_curve, token_amount), // bell
+        5 => calculate_integral_sol_in(curve, custom_curve, token_amount), // custom
         _ => Err(ProgramError::InvalidInstructionData.into()),
     }
 }
@@ -33,15 +48,55 @@ fn calculate_linear_sol_out(curve: &Curve, token_amount: u64) -> Result<u64> {
     Ok((token_amount * curve.reserve_sol) / curve.total_supply)
 }
 
-// TODO: Implement other curve calculations (exponential, logarithmic, sigmoid, bell, custom)
-fn calculate_exponential_tokens_out(curve: &Curve, sol_amount: u64) -> Result<u64> {
-    // Implement exponential curve calculation
-    unimplemented!()
+// exponential, logarithmic, sigmoid, bell and custom price by the area under the
+// amm::curves price function of the tokens sold so far. custom_params per curve_type:
+//   1 exponential: [base_price, scale]
+//   2 logarithmic: [base_price, slope, scale]
+//   3 sigmoid: [base_price, height, width], centered on half the supply
+//   4 bell: [base_price, peak, width], centered on half the supply
+//   5 custom: unused, the breakpoints live in the mint's CustomCurve account
+fn integral_curve(curve: &Curve, custom_curve: Option<&CustomCurve>) -> Result<Box<dyn BondingCurveModel>> {
+    let [first, second, third] = curve.custom_params.map(|param| param as u128);
+    let midpoint = (curve.total_supply / 2) as u128;
+    let supply = curve.total_supply.saturating_sub(curve.reserve_token) as u128;
+    let real_sol_reserves = curve.reserve_sol as u128;
+    let real_token_reserves = curve.reserve_token as u128;
+
+    let model: Option<Box<dyn BondingCurveModel>> = match curve.curve_type {
+        1 => Exponential::new(first, second)
+            .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+        2 => Logarithmic::new(first, second, third)
+            .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+        3 => Sigmoid::new(first, second, midpoint, third)
+            .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+        4 => Bell::new(first, second, midpoint, third)
+            .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+        5 => custom_curve
+            .and_then(|custom_curve| custom_curve.price_function())
+            .map(|f| Box::new(SupplyCurve::new(f, supply, real_sol_reserves, real_token_reserves)) as _),
+        _ => None,
+    };
+
+    model.ok_or_else(|| ProgramError::InvalidInstructionData.into())
 }
 
-fn calculate_exponential_sol_out(curve: &Curve, token_amount: u64) -> Result<u64> {
-    // Implement exponential curve calculation for selling
-    unimplemented!()
+fn calculate_integral_tokens_out(curve: &Curve, custom_curve: Option<&CustomCurve>, sol_amount: u64) -> Result<u64> {
+    let tokens_out = integral_curve(curve, custom_curve)?
+        .tokens_for_sol(sol_amount as u128, 0)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    Ok(tokens_out as u64)
 }
 
-// ... Implement other curve calculation functions ...
\ No newline at end of file
+fn calculate_integral_sol_in(curve: &Curve, custom_curve: Option<&CustomCurve>, token_amount: u64) -> Result<u64> {
+    let sol_in = integral_curve(curve, custom_curve)?
+        .buy_quote(token_amount as u128)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    Ok(sol_in as u64)
+}
+
+fn calculate_integral_sol_out(curve: &Curve, custom_curve: Option<&CustomCurve>, token_amount: u64) -> Result<u64> {
+    let sol_out = integral_curve(curve, custom_curve)?
+        .sell_quote(token_amount as u128)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    Ok(sol_out as u64)
+}
@@ -1 +1,2 @@
-pub mod curve_calculations;
\ No newline at end of file
+pub mod curve_calculations;
+pub mod merkle;